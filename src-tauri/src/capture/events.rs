@@ -1,5 +1,6 @@
 use core_graphics::event::{
-    CGEvent, CGEventTap, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement, CGEventType,
+    CGEvent, CGEventFlags, CGEventTap, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement,
+    CGEventType, EventField,
 };
 use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
 use core_foundation::runloop::{CFRunLoop, kCFRunLoopCommonModes, kCFRunLoopDefaultMode};
@@ -7,26 +8,30 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use crate::models::events::{EventType, MouseButton, MouseEvent, RecordedEvents};
+use crate::capture::keys;
+use crate::models::events::{EventType, KeyEvent, Modifiers, MouseButton, MouseEvent, RecordedEvents};
+use crate::models::settings::KeyCapture;
 
-/// Records mouse events using CGEventTap.
+/// Records mouse and keyboard events using CGEventTap.
 /// Runs on a background thread and collects events into a shared buffer.
 pub struct EventRecorder {
     events: Arc<Mutex<RecordedEvents>>,
     is_recording: Arc<AtomicBool>,
     start_time: Option<Instant>,
+    key_capture: KeyCapture,
 }
 
 impl EventRecorder {
-    pub fn new(display_width: f64, display_height: f64) -> Self {
+    pub fn new(display_width: f64, display_height: f64, key_capture: KeyCapture) -> Self {
         Self {
             events: Arc::new(Mutex::new(RecordedEvents::new(display_width, display_height))),
             is_recording: Arc::new(AtomicBool::new(false)),
             start_time: None,
+            key_capture,
         }
     }
 
-    /// Start recording mouse events.
+    /// Start recording mouse and keyboard events.
     /// Spawns a background thread with a CGEventTap.
     pub fn start(&mut self) -> Result<(), String> {
         if self.is_recording.load(Ordering::SeqCst) {
@@ -40,6 +45,7 @@ impl EventRecorder {
         // Clear previous events
         if let Ok(mut events) = self.events.lock() {
             events.mouse_events.clear();
+            events.key_events.clear();
             events.recording_start_ms = std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
@@ -48,9 +54,10 @@ impl EventRecorder {
 
         let events = Arc::clone(&self.events);
         let is_recording = Arc::clone(&self.is_recording);
+        let key_capture = self.key_capture;
 
         std::thread::spawn(move || {
-            Self::run_event_tap(events, is_recording, start);
+            Self::run_event_tap(events, is_recording, start, key_capture);
         });
 
         log::info!("Event recording started");
@@ -69,8 +76,9 @@ impl EventRecorder {
             .clone();
 
         log::info!(
-            "Event recording stopped: {} events captured",
-            events.mouse_events.len()
+            "Event recording stopped: {} mouse and {} key events captured",
+            events.mouse_events.len(),
+            events.key_events.len()
         );
         Ok(events)
     }
@@ -79,8 +87,56 @@ impl EventRecorder {
         events: Arc<Mutex<RecordedEvents>>,
         is_recording: Arc<AtomicBool>,
         start_time: Instant,
+        key_capture: KeyCapture,
     ) {
-        let events_of_interest = vec![
+        // Clone for the tap callback so we can still use originals in the fallback
+        let create_tap = |key_capture| {
+            Self::create_tap(Arc::clone(&events), Arc::clone(&is_recording), start_time, key_capture)
+        };
+        let mut tap = create_tap(key_capture);
+        if tap.is_err() && key_capture != KeyCapture::Off {
+            // Keyboard taps need Input Monitoring permission; keep mouse tracking without it
+            log::warn!("Failed to tap keyboard events. Input Monitoring permission may be required.");
+            tap = create_tap(KeyCapture::Off);
+        }
+
+        match tap {
+            Ok(tap) => unsafe {
+                let loop_source = tap
+                    .mach_port
+                    .create_runloop_source(0)
+                    .expect("Failed to create run loop source");
+
+                let current_loop = CFRunLoop::get_current();
+                current_loop.add_source(&loop_source, kCFRunLoopCommonModes);
+                tap.enable();
+
+                // Run until recording stops
+                while is_recording.load(Ordering::SeqCst) {
+                    CFRunLoop::run_in_mode(
+                        kCFRunLoopDefaultMode,
+                        Duration::from_millis(100),
+                        false,
+                    );
+                }
+            },
+            Err(()) => {
+                log::error!(
+                    "Failed to create CGEventTap. Accessibility permission may be required."
+                );
+                // Fall back to polling mouse position
+                Self::run_polling_fallback(events, is_recording, start_time);
+            }
+        }
+    }
+
+    fn create_tap(
+        events: Arc<Mutex<RecordedEvents>>,
+        is_recording: Arc<AtomicBool>,
+        start_time: Instant,
+        key_capture: KeyCapture,
+    ) -> Result<CGEventTap<'static>, ()> {
+        let mut events_of_interest = vec![
             CGEventType::LeftMouseDown,
             CGEventType::LeftMouseUp,
             CGEventType::RightMouseDown,
@@ -90,24 +146,33 @@ impl EventRecorder {
             CGEventType::LeftMouseDragged,
             CGEventType::RightMouseDragged,
         ];
+        if key_capture != KeyCapture::Off {
+            events_of_interest.push(CGEventType::KeyDown);
+        }
 
-        // Clone for the closure so we can still use originals in the fallback
-        let events_for_tap = Arc::clone(&events);
-        let is_recording_for_tap = Arc::clone(&is_recording);
-
-        let tap = CGEventTap::new(
+        CGEventTap::new(
             CGEventTapLocation::HID,
             CGEventTapPlacement::HeadInsertEventTap,
             CGEventTapOptions::ListenOnly,
             events_of_interest,
-            |_proxy, event_type, event| {
-                if !is_recording_for_tap.load(Ordering::SeqCst) {
+            move |_proxy, event_type, event| {
+                if !is_recording.load(Ordering::SeqCst) {
                     return None;
                 }
 
-                let location = event.location();
                 let timestamp_ms = start_time.elapsed().as_millis() as u64;
+                if matches!(event_type, CGEventType::KeyDown) {
+                    if let Some(key_event) = Self::key_event(event, timestamp_ms) {
+                        if key_capture.records(&key_event) {
+                            if let Ok(mut evts) = events.lock() {
+                                evts.key_events.push(key_event);
+                            }
+                        }
+                    }
+                    return None;
+                }
 
+                let location = event.location();
                 let (evt_type, button) = match event_type {
                     CGEventType::LeftMouseDown => (EventType::Click, MouseButton::Left),
                     CGEventType::RightMouseDown => (EventType::Click, MouseButton::Right),
@@ -126,42 +191,33 @@ impl EventRecorder {
                     button,
                 };
 
-                if let Ok(mut evts) = events_for_tap.lock() {
+                if let Ok(mut evts) = events.lock() {
                     evts.mouse_events.push(mouse_event);
                 }
 
                 None // Don't modify the event
             },
-        );
-
-        match tap {
-            Ok(tap) => unsafe {
-                let loop_source = tap
-                    .mach_port
-                    .create_runloop_source(0)
-                    .expect("Failed to create run loop source");
-
-                let current_loop = CFRunLoop::get_current();
-                current_loop.add_source(&loop_source, kCFRunLoopCommonModes);
-                tap.enable();
+        )
+    }
 
-                // Run until recording stops
-                while is_recording.load(Ordering::SeqCst) {
-                    CFRunLoop::run_in_mode(
-                        kCFRunLoopDefaultMode,
-                        Duration::from_millis(100),
-                        false,
-                    );
-                }
-            },
-            Err(()) => {
-                log::error!(
-                    "Failed to create CGEventTap. Accessibility permission may be required."
-                );
-                // Fall back to polling mouse position
-                Self::run_polling_fallback(events, is_recording, start_time);
-            }
+    /// Convert a key-down event, skipping auto-repeats and unnamed keys
+    fn key_event(event: &CGEvent, timestamp_ms: u64) -> Option<KeyEvent> {
+        if event.get_integer_value_field(EventField::KEYBOARD_EVENT_AUTOREPEAT) != 0 {
+            return None;
         }
+        let keycode = event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE) as u16;
+        let key = keys::key_name(keycode)?;
+        let flags = event.get_flags();
+        Some(KeyEvent {
+            timestamp_ms,
+            key: key.to_string(),
+            modifiers: Modifiers {
+                command: flags.contains(CGEventFlags::CGEventFlagCommand),
+                shift: flags.contains(CGEventFlags::CGEventFlagShift),
+                option: flags.contains(CGEventFlags::CGEventFlagAlternate),
+                control: flags.contains(CGEventFlags::CGEventFlagControl),
+            },
+        })
     }
 
     /// Fallback: poll mouse position if CGEventTap isn't available
@@ -0,0 +1,106 @@
+/// Name of the key with macOS virtual keycode `keycode`.
+///
+/// Keycodes identify physical key positions, so names follow the US ANSI
+/// layout regardless of the active input source. Modifier keys and keys
+/// without a useful label return `None`.
+pub fn key_name(keycode: u16) -> Option<&'static str> {
+    let name = match keycode {
+        0x00 => "A",
+        0x01 => "S",
+        0x02 => "D",
+        0x03 => "F",
+        0x04 => "H",
+        0x05 => "G",
+        0x06 => "Z",
+        0x07 => "X",
+        0x08 => "C",
+        0x09 => "V",
+        0x0B => "B",
+        0x0C => "Q",
+        0x0D => "W",
+        0x0E => "E",
+        0x0F => "R",
+        0x10 => "Y",
+        0x11 => "T",
+        0x12 => "1",
+        0x13 => "2",
+        0x14 => "3",
+        0x15 => "4",
+        0x16 => "6",
+        0x17 => "5",
+        0x18 => "=",
+        0x19 => "9",
+        0x1A => "7",
+        0x1B => "-",
+        0x1C => "8",
+        0x1D => "0",
+        0x1E => "]",
+        0x1F => "O",
+        0x20 => "U",
+        0x21 => "[",
+        0x22 => "I",
+        0x23 => "P",
+        0x24 => "Return",
+        0x25 => "L",
+        0x26 => "J",
+        0x27 => "'",
+        0x28 => "K",
+        0x29 => ";",
+        0x2A => "\\",
+        0x2B => ",",
+        0x2C => "/",
+        0x2D => "N",
+        0x2E => "M",
+        0x2F => ".",
+        0x30 => "Tab",
+        0x31 => "Space",
+        0x32 => "`",
+        0x33 => "Delete",
+        0x35 => "Escape",
+        0x4C => "Enter",
+        0x60 => "F5",
+        0x61 => "F6",
+        0x62 => "F7",
+        0x63 => "F3",
+        0x64 => "F8",
+        0x65 => "F9",
+        0x67 => "F11",
+        0x6D => "F10",
+        0x6F => "F12",
+        0x73 => "Home",
+        0x74 => "PageUp",
+        0x75 => "ForwardDelete",
+        0x76 => "F4",
+        0x77 => "End",
+        0x78 => "F2",
+        0x79 => "PageDown",
+        0x7A => "F1",
+        0x7B => "Left",
+        0x7C => "Right",
+        0x7D => "Down",
+        0x7E => "Up",
+        _ => return None,
+    };
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_name() {
+        assert_eq!(key_name(0x23), Some("P"));
+        assert_eq!(key_name(0x1D), Some("0"));
+        assert_eq!(key_name(0x24), Some("Return"));
+        assert_eq!(key_name(0x7B), Some("Left"));
+    }
+
+    #[test]
+    fn test_modifier_keys_have_no_name() {
+        // Command, shift and option
+        assert_eq!(key_name(0x37), None);
+        assert_eq!(key_name(0x38), None);
+        assert_eq!(key_name(0x3A), None);
+    }
+}
@@ -1,3 +1,4 @@
 pub mod events;
+pub mod keys;
 pub mod screen;
 pub mod session;
@@ -71,6 +71,10 @@ pub struct ScreenRecorder {
 }
 
 impl ScreenRecorder {
+    /// Recordings leave out the system cursor; exports draw a smoothed,
+    /// scalable one from the recorded mouse events instead
+    pub const CAPTURES_CURSOR: bool = false;
+
     pub fn new(output_path: PathBuf, display_id: u32) -> Self {
         Self {
             output_path,
@@ -93,8 +97,10 @@ impl ScreenRecorder {
         // -D flag selects display by index (1-based)
         let mut cmd = Command::new("screencapture");
         cmd.arg("-v") // video mode
-            .arg("-C") // capture cursor
             .arg("-x"); // no sound effects
+        if Self::CAPTURES_CURSOR {
+            cmd.arg("-C"); // capture cursor
+        }
 
         // Add display selection if not main display
         if self.display_id != 0 {
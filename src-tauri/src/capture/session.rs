@@ -5,7 +5,9 @@ use crate::capture::events::EventRecorder;
 use crate::capture::screen::ScreenRecorder;
 use crate::models::events::RecordedEvents;
 use crate::models::project::{DisplayInfo, Project, RecordingState};
-use crate::utils::paths;
+use crate::models::settings::KeyCapture;
+use crate::processing::thumbnails;
+use crate::utils::{paths, settings};
 
 /// Manages the lifecycle of a recording session.
 /// Coordinates screen capture and event recording.
@@ -52,8 +54,14 @@ impl RecordingSession {
         screen_recorder.start()?;
 
         // Initialize event recorder
+        let key_capture = settings::load()
+            .map(|settings| settings.key_capture)
+            .unwrap_or_else(|e| {
+                log::warn!("Ignoring app settings: {}", e);
+                KeyCapture::default()
+            });
         let mut event_recorder =
-            EventRecorder::new(display.width as f64, display.height as f64);
+            EventRecorder::new(display.width as f64, display.height as f64, key_capture);
         event_recorder.start()?;
 
         self.screen_recorder = Some(screen_recorder);
@@ -124,6 +132,11 @@ impl RecordingSession {
             60.0,
         );
         project.duration_ms = duration_ms;
+        project.cursor_in_video = ScreenRecorder::CAPTURES_CURSOR;
+        match settings::load() {
+            Ok(settings) => settings.apply_to(&mut project),
+            Err(e) => log::warn!("Ignoring app settings: {}", e),
+        }
 
         // Save project metadata
         let project_json = serde_json::to_string_pretty(&project)
@@ -131,6 +144,12 @@ impl RecordingSession {
         std::fs::write(paths::project_metadata_path(&project_id), project_json)
             .map_err(|e| format!("Failed to write project: {}", e))?;
 
+        // The library falls back to text without a thumbnail, so this isn't fatal
+        let thumbnail_path = paths::project_thumbnail_path(&project);
+        if let Err(e) = thumbnails::generate_thumbnail(&project, &thumbnail_path) {
+            log::warn!("Failed to generate thumbnail: {}", e);
+        }
+
         // Reset state
         self.screen_recorder = None;
         self.event_recorder = None;
@@ -1,6 +1,7 @@
 use crate::models::effects::{FrameStyle, ZoomConfig};
 use crate::models::events::RecordedEvents;
-use crate::models::project::Project;
+use crate::models::project::{Project, ProjectSummary};
+use crate::processing::thumbnails;
 use crate::utils::paths;
 
 #[tauri::command]
@@ -25,14 +26,47 @@ pub fn load_events(project_id: String) -> Result<RecordedEvents, String> {
 
 #[tauri::command]
 pub fn save_project(project: Project) -> Result<(), String> {
+    let redactions_changed = load_project(project.id.clone())
+        .map(|saved| saved.redactions != project.redactions)
+        .unwrap_or(false);
+
     let path = paths::project_metadata_path(&project.id);
     let json = serde_json::to_string_pretty(&project)
         .map_err(|e| format!("Failed to serialize: {}", e))?;
     std::fs::write(&path, json)
         .map_err(|e| format!("Failed to write project: {}", e))?;
+
+    if redactions_changed {
+        refresh_thumbnails(project);
+    }
     Ok(())
 }
 
+/// Cached previews are named after the redactions they show, so once those
+/// change the old ones are never served again. This clears them out, and
+/// remakes the poster in the background; filmstrips are remade on their
+/// next request.
+fn refresh_thumbnails(project: Project) {
+    let thumbnail_path = paths::project_thumbnail_path(&project);
+    for path in paths::project_preview_paths(&project.id) {
+        if path == thumbnail_path {
+            continue;
+        }
+        if let Err(e) = std::fs::remove_file(&path) {
+            log::warn!("Failed to remove {}: {}", path.display(), e);
+        }
+    }
+    if thumbnail_path.exists() {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        if let Err(e) = thumbnails::generate_thumbnail(&project, &thumbnail_path) {
+            log::warn!("Failed to regenerate thumbnail: {}", e);
+        }
+    });
+}
+
 #[tauri::command]
 pub fn update_zoom_config(project_id: String, zoom_config: ZoomConfig) -> Result<(), String> {
     let mut project = load_project(project_id)?;
@@ -49,6 +83,31 @@ pub fn update_frame_style(project_id: String, frame_style: FrameStyle) -> Result
 
 #[tauri::command]
 pub fn list_projects() -> Result<Vec<Project>, String> {
+    read_projects()
+}
+
+/// Lightweight listing for the project library, with poster thumbnails
+/// where one has been generated
+#[tauri::command]
+pub fn list_project_summaries() -> Result<Vec<ProjectSummary>, String> {
+    let summaries = read_projects()?
+        .into_iter()
+        .map(|project| {
+            let thumbnail = paths::project_thumbnail_path(&project);
+            ProjectSummary {
+                thumbnail_path: thumbnail.exists().then(|| thumbnail.to_string_lossy().to_string()),
+                id: project.id,
+                name: project.name,
+                created_at: project.created_at,
+                duration_ms: project.duration_ms,
+            }
+        })
+        .collect();
+    Ok(summaries)
+}
+
+/// Every readable project, newest first
+fn read_projects() -> Result<Vec<Project>, String> {
     let dir = paths::projects_dir();
     let mut projects = Vec::new();
 
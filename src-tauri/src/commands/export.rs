@@ -1,6 +1,6 @@
 use tauri::{Emitter, AppHandle};
 
-use crate::commands::editing::load_project;
+use crate::commands::editing::{load_events, load_project};
 use crate::models::effects::ExportConfig;
 use crate::processing::encoder;
 
@@ -10,11 +10,12 @@ pub async fn export_project(
     project_id: String,
     config: ExportConfig,
 ) -> Result<String, String> {
-    let project = load_project(project_id)?;
+    let project = load_project(project_id.clone())?;
+    let events = load_events(project_id)?;
 
     let app_handle = app.clone();
     let result = tokio::task::spawn_blocking(move || {
-        encoder::export_project(&project, &config, move |progress| {
+        encoder::export_project(&project, &events, &config, move |progress| {
             let _ = app_handle.emit("export-progress", progress);
         })
     })
@@ -1,4 +1,6 @@
 pub mod editing;
 pub mod export;
+pub mod preview;
 pub mod recording;
+pub mod settings;
 pub mod system;
@@ -0,0 +1,39 @@
+use tauri::ipc::Response;
+
+use crate::commands::editing::{load_events, load_project};
+use crate::processing::{preview, thumbnails};
+use crate::utils::paths;
+
+/// Returns the composed frame as PNG bytes, which arrive in the frontend
+/// as an `ArrayBuffer`
+#[tauri::command]
+pub async fn render_preview_frame(project_id: String, time_ms: u64, max_width: u32) -> Result<Response, String> {
+    let project = load_project(project_id.clone())?;
+    let events = load_events(project_id)?;
+
+    let png = tokio::task::spawn_blocking(move || {
+        preview::render_preview_frame(&project, &events, time_ms, max_width)
+    })
+    .await
+    .map_err(|e| format!("Preview task failed: {}", e))??;
+
+    Ok(Response::new(png))
+}
+
+/// Path of a sprite sheet with `frame_count` frames across the recording,
+/// generated on first request and cached in the project directory. The
+/// cache is keyed on the redactions, so edits never serve a stale strip.
+#[tauri::command]
+pub async fn get_project_filmstrip(project_id: String, frame_count: u32, frame_height: u32) -> Result<String, String> {
+    let project = load_project(project_id.clone())?;
+    let path = paths::project_filmstrip_path(&project, frame_count, frame_height);
+    if !path.exists() {
+        let output = path.clone();
+        tokio::task::spawn_blocking(move || {
+            thumbnails::generate_filmstrip(&project, frame_count, frame_height, &output)
+        })
+        .await
+        .map_err(|e| format!("Filmstrip task failed: {}", e))??;
+    }
+    Ok(path.to_string_lossy().to_string())
+}
@@ -0,0 +1,12 @@
+use crate::models::settings::AppSettings;
+use crate::utils;
+
+#[tauri::command]
+pub fn get_settings() -> Result<AppSettings, String> {
+    utils::settings::load()
+}
+
+#[tauri::command]
+pub fn save_settings(settings: AppSettings) -> Result<(), String> {
+    utils::settings::save(&settings)
+}
@@ -21,6 +21,9 @@ pub fn run() {
             commands::system::get_displays,
             commands::system::check_permissions,
             commands::system::get_app_version,
+            // Settings
+            commands::settings::get_settings,
+            commands::settings::save_settings,
             // Recording
             commands::recording::start_recording,
             commands::recording::stop_recording,
@@ -33,11 +36,15 @@ pub fn run() {
             commands::editing::update_zoom_config,
             commands::editing::update_frame_style,
             commands::editing::list_projects,
+            commands::editing::list_project_summaries,
             commands::editing::delete_project,
             // Export
             commands::export::export_project,
             commands::export::get_video_duration,
             commands::export::check_ffmpeg,
+            // Preview
+            commands::preview::render_preview_frame,
+            commands::preview::get_project_filmstrip,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+
+/// Coordinate space an annotation is positioned in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum AnnotationSpace {
+    /// Source video pixels; the annotation follows the zoom viewport
+    #[default]
+    Source,
+    /// Output canvas pixels; the annotation stays put while zooming
+    Canvas,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AnnotationKind {
+    /// Text with its top-left corner at the annotation position. Newlines
+    /// start a new line.
+    Text {
+        text: String,
+        /// Font size in pixels
+        size: f64,
+        /// .ttf/.otf/.ttc file, the system UI font when unset
+        #[serde(default)]
+        font_path: Option<String>,
+        /// Hex color of a rounded box drawn behind the text
+        #[serde(default)]
+        background: Option<String>,
+    },
+    /// Arrow from the annotation position to (to_x, to_y)
+    Arrow { to_x: f64, to_y: f64, width: f64 },
+    /// Outlined rectangle with its top-left corner at the annotation position
+    Rectangle {
+        width: f64,
+        height: f64,
+        stroke_width: f64,
+        #[serde(default)]
+        corner_radius: f64,
+        /// Hex fill color, unfilled when unset
+        #[serde(default)]
+        fill: Option<String>,
+    },
+    /// Translucent marker box, like a highlighter pen
+    Highlight {
+        width: f64,
+        height: f64,
+        #[serde(default)]
+        corner_radius: f64,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    pub id: String,
+    pub kind: AnnotationKind,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub x: f64,
+    pub y: f64,
+    #[serde(default)]
+    pub space: AnnotationSpace,
+    /// Hex color
+    pub color: String,
+    /// 0.0 - 1.0
+    #[serde(default = "default_opacity")]
+    pub opacity: f64,
+    #[serde(default)]
+    pub fade_in_ms: u64,
+    #[serde(default)]
+    pub fade_out_ms: u64,
+}
+
+fn default_opacity() -> f64 {
+    1.0
+}
+
+impl Annotation {
+    /// Opacity multiplier at `time_ms`, 0.0 outside the annotation's
+    /// time range
+    pub fn fade_at(&self, time_ms: f64) -> f64 {
+        let (start, end) = (self.start_ms as f64, self.end_ms as f64);
+        if time_ms < start || time_ms >= end {
+            return 0.0;
+        }
+        let fade_in = if self.fade_in_ms > 0 {
+            (time_ms - start) / self.fade_in_ms as f64
+        } else {
+            1.0
+        };
+        let fade_out = if self.fade_out_ms > 0 {
+            (end - time_ms) / self.fade_out_ms as f64
+        } else {
+            1.0
+        };
+        fade_in.min(fade_out).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn annotation(start_ms: u64, end_ms: u64, fade_in_ms: u64, fade_out_ms: u64) -> Annotation {
+        Annotation {
+            id: "a".to_string(),
+            kind: AnnotationKind::Arrow { to_x: 10.0, to_y: 10.0, width: 4.0 },
+            start_ms,
+            end_ms,
+            x: 0.0,
+            y: 0.0,
+            space: AnnotationSpace::Source,
+            color: "#FF0000".to_string(),
+            opacity: 1.0,
+            fade_in_ms,
+            fade_out_ms,
+        }
+    }
+
+    #[test]
+    fn test_fade_at() {
+        let a = annotation(1000, 3000, 200, 400);
+        assert_eq!(a.fade_at(999.0), 0.0);
+        assert!((a.fade_at(1100.0) - 0.5).abs() < 1e-9);
+        assert_eq!(a.fade_at(2000.0), 1.0);
+        assert!((a.fade_at(2800.0) - 0.5).abs() < 1e-9);
+        assert_eq!(a.fade_at(3000.0), 0.0);
+    }
+
+    #[test]
+    fn test_fade_at_without_fades() {
+        let a = annotation(0, 500, 0, 0);
+        assert_eq!(a.fade_at(0.0), 1.0);
+        assert_eq!(a.fade_at(499.0), 1.0);
+    }
+
+    #[test]
+    fn test_annotation_json_defaults() {
+        let json = r##"{
+            "id": "t1",
+            "kind": { "Text": { "text": "Hello", "size": 32 } },
+            "start_ms": 0,
+            "end_ms": 1000,
+            "x": 10,
+            "y": 20,
+            "color": "#FFFFFF"
+        }"##;
+        let a: Annotation = serde_json::from_str(json).unwrap();
+        assert_eq!(a.space, AnnotationSpace::Source);
+        assert_eq!(a.opacity, 1.0);
+        assert_eq!(a.fade_in_ms, 0);
+        assert!(matches!(a.kind, AnnotationKind::Text { font_path: None, background: None, .. }));
+    }
+}
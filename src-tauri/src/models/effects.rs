@@ -26,6 +26,32 @@ pub struct ZoomConfig {
     /// ms, default 300
     pub zoom_out_duration_ms: u64,
     pub easing: EasingType,
+    /// Blend sub-frame viewports while the zoom is moving
+    #[serde(default)]
+    pub motion_blur: bool,
+    /// Degrees of each frame interval the shutter stays open, 0 - 360, default 180
+    #[serde(default = "default_shutter_angle")]
+    pub shutter_angle: f64,
+    /// Clicks at most this far apart in time (ms) share one zoom, 0 to
+    /// zoom on every click separately, default 1500
+    #[serde(default = "default_cluster_gap_ms")]
+    pub cluster_gap_ms: u64,
+    /// ...if they are also at most this far apart, as a fraction of the
+    /// frame width, default 0.35
+    #[serde(default = "default_cluster_distance")]
+    pub cluster_distance: f64,
+}
+
+fn default_shutter_angle() -> f64 {
+    180.0
+}
+
+fn default_cluster_gap_ms() -> u64 {
+    1500
+}
+
+fn default_cluster_distance() -> f64 {
+    0.35
 }
 
 impl Default for ZoomConfig {
@@ -37,17 +63,83 @@ impl Default for ZoomConfig {
             hold_duration_ms: 500,
             zoom_out_duration_ms: 300,
             easing: EasingType::EaseInOut,
+            motion_blur: false,
+            shutter_angle: default_shutter_angle(),
+            cluster_gap_ms: default_cluster_gap_ms(),
+            cluster_distance: default_cluster_distance(),
         }
     }
 }
 
+/// A user-supplied cursor sprite
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CursorImage {
+    /// Path to a PNG with transparency
+    pub path: String,
+    /// Click point within the image, in image pixels
+    pub hotspot_x: f64,
+    pub hotspot_y: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CursorConfig {
     pub smoothing: f64,
     pub auto_hide_after_ms: u64,
     pub highlight_clicks: bool,
     pub highlight_color: String,
+    /// Click ring radius in display points
     pub highlight_radius: u32,
+    /// Cursor size multiplier, default 1.0
+    #[serde(default = "default_cursor_scale")]
+    pub scale: f64,
+    /// Custom cursor sprite; the built-in arrow is used when unset
+    #[serde(default)]
+    pub custom_image: Option<CursorImage>,
+    /// Dims everything outside a soft circle around the cursor
+    #[serde(default)]
+    pub spotlight: SpotlightMode,
+    /// Radius of the undimmed circle in display points, default 160
+    #[serde(default = "default_spotlight_radius")]
+    pub spotlight_radius: u32,
+    /// Width of the soft edge outside the radius in display points, default 80
+    #[serde(default = "default_spotlight_feather")]
+    pub spotlight_feather: u32,
+    /// How much the surroundings are darkened, 0.0 - 1.0, default 0.6
+    #[serde(default = "default_spotlight_dim")]
+    pub spotlight_dim: f64,
+}
+
+fn default_cursor_scale() -> f64 {
+    1.0
+}
+
+fn default_spotlight_radius() -> u32 {
+    160
+}
+
+fn default_spotlight_feather() -> u32 {
+    80
+}
+
+fn default_spotlight_dim() -> f64 {
+    0.6
+}
+
+/// When the cursor spotlight is shown
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum SpotlightMode {
+    #[default]
+    Off,
+    /// Whenever the cursor is moving
+    WhileMoving,
+    /// During the given time ranges
+    Ranges { ranges: Vec<SpotlightRange> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpotlightRange {
+    pub start_ms: u64,
+    pub end_ms: u64,
 }
 
 impl Default for CursorConfig {
@@ -58,15 +150,81 @@ impl Default for CursorConfig {
             highlight_clicks: true,
             highlight_color: "#FFD700".to_string(),
             highlight_radius: 30,
+            scale: default_cursor_scale(),
+            custom_image: None,
+            spotlight: SpotlightMode::default(),
+            spotlight_radius: default_spotlight_radius(),
+            spotlight_feather: default_spotlight_feather(),
+            spotlight_dim: default_spotlight_dim(),
         }
     }
 }
 
+/// How a background image is mapped onto the canvas
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum ImageFit {
+    /// Scale to fill the canvas, cropping the overflow
+    #[default]
+    Cover,
+    /// Scale to fit inside the canvas, letterboxing the rest
+    Contain,
+    /// Scale each axis independently to the canvas size
+    Stretch,
+    /// Repeat the image at its native size
+    Tile,
+}
+
+/// Shape of a gradient background
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum GradientKind {
+    /// Straight bands along `angle`
+    #[default]
+    Linear,
+    /// Rings spreading out from the center
+    Radial,
+    /// Sweeps around the center, starting at `angle`
+    Conic,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Background {
     Solid { color: String },
-    Gradient { colors: Vec<String>, angle: f64 },
-    Image { path: String },
+    Gradient {
+        colors: Vec<String>,
+        /// Degrees
+        angle: f64,
+        #[serde(default)]
+        kind: GradientKind,
+        /// Position of each color, 0.0 - 1.0; evenly spaced when empty
+        #[serde(default)]
+        stops: Vec<f64>,
+        /// Center of radial and conic gradients, as a fraction of the canvas
+        #[serde(default = "default_gradient_center")]
+        center_x: f64,
+        #[serde(default = "default_gradient_center")]
+        center_y: f64,
+        /// Add a little noise to hide banding
+        #[serde(default)]
+        dither: bool,
+    },
+    Image {
+        path: String,
+        #[serde(default)]
+        fit: ImageFit,
+        /// Gaussian blur sigma in canvas pixels, 0 = sharp
+        #[serde(default)]
+        blur: f64,
+        /// 0.0 - 1.0, how much to darken the image
+        #[serde(default)]
+        dim: f64,
+    },
+    /// No background, so only the frame and its shadow are opaque. Needs an
+    /// export format with an alpha channel.
+    Transparent,
+}
+
+fn default_gradient_center() -> f64 {
+    0.5
 }
 
 impl Default for Background {
@@ -74,6 +232,11 @@ impl Default for Background {
         Self::Gradient {
             colors: vec!["#667eea".to_string(), "#764ba2".to_string()],
             angle: 135.0,
+            kind: GradientKind::default(),
+            stops: Vec::new(),
+            center_x: default_gradient_center(),
+            center_y: default_gradient_center(),
+            dither: false,
         }
     }
 }
@@ -83,6 +246,9 @@ pub struct Shadow {
     pub offset_x: f64,
     pub offset_y: f64,
     pub blur: f64,
+    /// Grows (or with a negative value shrinks) the shadow shape, in pixels
+    #[serde(default)]
+    pub spread: f64,
     pub color: String,
     pub opacity: f64,
 }
@@ -93,6 +259,7 @@ impl Default for Shadow {
             offset_x: 0.0,
             offset_y: 8.0,
             blur: 32.0,
+            spread: 0.0,
             color: "#000000".to_string(),
             opacity: 0.3,
         }
@@ -113,12 +280,176 @@ impl Default for AspectRatio {
     }
 }
 
+impl AspectRatio {
+    /// Width / height, or `None` to follow the recording
+    pub fn ratio(&self) -> Option<f64> {
+        match self {
+            Self::Auto => None,
+            Self::Ratio16x9 => Some(16.0 / 9.0),
+            Self::Ratio9x16 => Some(9.0 / 16.0),
+            Self::Ratio1x1 => Some(1.0),
+        }
+    }
+}
+
+/// Shape of the frame's rounded corners
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum CornerStyle {
+    /// Circular arcs
+    #[default]
+    Circular,
+    /// Superellipse corners with a smoother, continuous curvature
+    Squircle,
+}
+
+/// Outline drawn around the outside of the frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Border {
+    /// Pixels
+    pub width: f64,
+    pub color: String,
+    /// 0.0 - 1.0
+    pub opacity: f64,
+}
+
+/// Device or window decoration drawn around the recording
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum FrameChrome {
+    #[default]
+    None,
+    /// macOS-style title bar with traffic lights
+    Window {
+        #[serde(default)]
+        title: String,
+        #[serde(default)]
+        dark: bool,
+    },
+    /// Browser toolbar with an address field
+    Browser {
+        url: String,
+        #[serde(default)]
+        dark: bool,
+    },
+    /// Laptop screen bezel and base
+    Laptop,
+}
+
+/// On-screen badges showing the keys pressed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystrokeConfig {
+    pub enabled: bool,
+    /// How long a badge stays up after the last key press, ms, default 1500
+    pub display_ms: u64,
+    /// Badge text size in canvas pixels, default 40
+    pub font_size: f64,
+}
+
+impl Default for KeystrokeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            display_ms: 1500,
+            font_size: 40.0,
+        }
+    }
+}
+
+/// Color correction for the recording itself; backgrounds, overlays and
+/// annotations are left as they are
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColorGrade {
+    /// -1.0 - 1.0, 0.0 leaves the recording unchanged
+    #[serde(default)]
+    pub brightness: f64,
+    /// -1.0 - 1.0
+    #[serde(default)]
+    pub contrast: f64,
+    /// -1.0 (grayscale) - 1.0
+    #[serde(default)]
+    pub saturation: f64,
+    /// Applied after the adjustments above
+    #[serde(default)]
+    pub lut: Option<LutConfig>,
+}
+
+impl Default for ColorGrade {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            contrast: 0.0,
+            saturation: 0.0,
+            lut: None,
+        }
+    }
+}
+
+/// A 3D LUT in Adobe/Resolve `.cube` format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LutConfig {
+    pub path: String,
+    /// Mix between the original (0.0) and fully graded (1.0) color, default 1.0
+    #[serde(default = "default_lut_strength")]
+    pub strength: f64,
+}
+
+fn default_lut_strength() -> f64 {
+    1.0
+}
+
+/// Canvas corner a watermark is pinned to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum WatermarkAnchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+/// A logo drawn on top of everything, fixed to the canvas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Watermark {
+    /// Path to an image, usually a PNG with transparency
+    pub path: String,
+    #[serde(default)]
+    pub anchor: WatermarkAnchor,
+    /// Gap to the canvas edges in pixels, default 24
+    #[serde(default = "default_watermark_margin")]
+    pub margin: u32,
+    /// Logo width as a fraction of the canvas width, default 0.12
+    #[serde(default = "default_watermark_scale")]
+    pub scale: f64,
+    /// 0.0 - 1.0, default 0.8
+    #[serde(default = "default_watermark_opacity")]
+    pub opacity: f64,
+}
+
+fn default_watermark_margin() -> u32 {
+    24
+}
+
+fn default_watermark_scale() -> f64 {
+    0.12
+}
+
+fn default_watermark_opacity() -> f64 {
+    0.8
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FrameStyle {
     pub background: Background,
     pub padding: u32,
     pub corner_radius: u32,
+    #[serde(default)]
+    pub corner_style: CornerStyle,
     pub shadow: Shadow,
+    #[serde(default)]
+    pub border: Option<Border>,
+    #[serde(default)]
+    pub chrome: FrameChrome,
+    #[serde(default)]
+    pub watermark: Option<Watermark>,
     pub aspect_ratio: AspectRatio,
 }
 
@@ -128,7 +459,11 @@ impl Default for FrameStyle {
             background: Background::default(),
             padding: 64,
             corner_radius: 12,
+            corner_style: CornerStyle::Circular,
             shadow: Shadow::default(),
+            border: None,
+            chrome: FrameChrome::None,
+            watermark: None,
             aspect_ratio: AspectRatio::Auto,
         }
     }
@@ -138,6 +473,12 @@ impl Default for FrameStyle {
 pub enum ExportFormat {
     Mp4,
     Gif,
+    /// VP9 WebM with an alpha channel
+    WebM,
+    /// ProRes 4444 .mov with an alpha channel
+    ProRes,
+    /// Animated PNG with an alpha channel
+    Apng,
 }
 
 impl Default for ExportFormat {
@@ -146,6 +487,13 @@ impl Default for ExportFormat {
     }
 }
 
+impl ExportFormat {
+    /// Whether the output keeps transparent pixels transparent
+    pub fn supports_alpha(&self) -> bool {
+        matches!(self, Self::WebM | Self::ProRes | Self::Apng)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ExportResolution {
     R720p,
@@ -169,6 +517,30 @@ impl ExportResolution {
             Self::Custom { width, height } => (*width, *height),
         }
     }
+
+    /// Output size for a canvas of the given size, keeping its aspect ratio.
+    ///
+    /// Presets name the short edge (a 1080p vertical export is 1080x1920);
+    /// custom sizes are a bounding box the canvas is fitted into. Both
+    /// dimensions are rounded to even numbers for the H.264 encoder.
+    pub fn fit_canvas(&self, canvas_width: u32, canvas_height: u32) -> (u32, u32) {
+        let cw = canvas_width.max(1) as f64;
+        let ch = canvas_height.max(1) as f64;
+
+        let scale = match self {
+            Self::Custom { width, height } => (*width as f64 / cw).min(*height as f64 / ch),
+            preset => {
+                let (_, short_edge) = preset.dimensions();
+                short_edge as f64 / cw.min(ch)
+            }
+        };
+
+        let even = |v: f64| {
+            let v = v.round() as u32;
+            (v - v % 2).max(2)
+        };
+        (even(cw * scale), even(ch * scale))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -178,6 +550,21 @@ pub struct ExportConfig {
     /// 0.0 - 1.0
     pub quality: f64,
     pub output_path: String,
+    /// Frames composed in parallel; 0 picks one per available CPU core
+    #[serde(default)]
+    pub worker_count: u32,
+}
+
+impl ExportConfig {
+    /// Resolve `worker_count` to the number of compositing threads to run
+    pub fn workers(&self) -> usize {
+        if self.worker_count > 0 {
+            return self.worker_count as usize;
+        }
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    }
 }
 
 impl Default for ExportConfig {
@@ -187,6 +574,7 @@ impl Default for ExportConfig {
             resolution: ExportResolution::default(),
             quality: 0.8,
             output_path: String::new(),
+            worker_count: 0,
         }
     }
 }
@@ -206,6 +594,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_export_resolution_fit_canvas_presets() {
+        assert_eq!(ExportResolution::R1080p.fit_canvas(3840, 2160), (1920, 1080));
+        assert_eq!(ExportResolution::R1080p.fit_canvas(2160, 3840), (1080, 1920));
+        assert_eq!(ExportResolution::R720p.fit_canvas(2000, 2000), (720, 720));
+        assert_eq!(ExportResolution::R4k.fit_canvas(2048, 1208), (3662, 2160));
+    }
+
+    #[test]
+    fn test_export_config_workers() {
+        let mut config = ExportConfig::default();
+        assert!(config.workers() >= 1);
+        config.worker_count = 3;
+        assert_eq!(config.workers(), 3);
+    }
+
+    #[test]
+    fn test_export_config_without_worker_count() {
+        let json = r#"{"format":"Mp4","resolution":"R1080p","quality":0.8,"output_path":""}"#;
+        let config: ExportConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.worker_count, 0);
+    }
+
+    #[test]
+    fn test_export_resolution_fit_canvas_custom() {
+        let res = ExportResolution::Custom { width: 800, height: 600 };
+        assert_eq!(res.fit_canvas(1600, 900), (800, 450));
+        assert_eq!(res.fit_canvas(1000, 1000), (600, 600));
+    }
+
+    #[test]
+    fn test_aspect_ratio_values() {
+        assert!(AspectRatio::Auto.ratio().is_none());
+        assert!((AspectRatio::Ratio9x16.ratio().unwrap() - 0.5625).abs() < 1e-10);
+    }
+
     #[test]
     fn test_zoom_config_serde_roundtrip() {
         let config = ZoomConfig::default();
@@ -216,6 +640,16 @@ mod tests {
         assert_eq!(deserialized.zoom_in_duration_ms, config.zoom_in_duration_ms);
     }
 
+    #[test]
+    fn test_cursor_config_legacy_json_defaults() {
+        let json = r##"{"smoothing":0.5,"auto_hide_after_ms":3000,"highlight_clicks":true,"highlight_color":"#FFD700","highlight_radius":30}"##;
+        let config: CursorConfig = serde_json::from_str(json).unwrap();
+        assert!((config.scale - 1.0).abs() < 1e-10);
+        assert!(config.custom_image.is_none());
+        assert_eq!(config.spotlight, SpotlightMode::Off);
+        assert_eq!(config.spotlight_radius, 160);
+    }
+
     #[test]
     fn test_frame_style_serde_roundtrip() {
         let style = FrameStyle::default();
@@ -225,6 +659,33 @@ mod tests {
         assert_eq!(deserialized.corner_radius, style.corner_radius);
     }
 
+    #[test]
+    fn test_frame_chrome_json() {
+        let chrome: FrameChrome = serde_json::from_str(r#""Laptop""#).unwrap();
+        assert!(matches!(chrome, FrameChrome::Laptop));
+
+        let chrome: FrameChrome = serde_json::from_str(r#"{"Browser":{"url":"example.com"}}"#).unwrap();
+        assert!(matches!(chrome, FrameChrome::Browser { ref url, dark: false } if url == "example.com"));
+
+        let style: FrameStyle = serde_json::from_str(
+            r##"{"background":{"Solid":{"color":"#000"}},"padding":0,"corner_radius":0,
+                "shadow":{"offset_x":0,"offset_y":0,"blur":0,"color":"#000","opacity":0},
+                "aspect_ratio":"Auto"}"##,
+        )
+        .unwrap();
+        assert!(matches!(style.chrome, FrameChrome::None));
+        assert!(style.watermark.is_none());
+    }
+
+    #[test]
+    fn test_watermark_json_defaults() {
+        let watermark: Watermark = serde_json::from_str(r#"{"path":"/tmp/logo.png"}"#).unwrap();
+        assert_eq!(watermark.anchor, WatermarkAnchor::BottomRight);
+        assert_eq!(watermark.margin, 24);
+        assert!((watermark.scale - 0.12).abs() < 1e-10);
+        assert!((watermark.opacity - 0.8).abs() < 1e-10);
+    }
+
     #[test]
     fn test_background_serde_roundtrip() {
         let backgrounds = vec![
@@ -232,8 +693,19 @@ mod tests {
             Background::Gradient {
                 colors: vec!["#000".to_string(), "#FFF".to_string()],
                 angle: 45.0,
+                kind: GradientKind::Conic,
+                stops: vec![0.0, 0.3],
+                center_x: 0.25,
+                center_y: 0.75,
+                dither: true,
             },
-            Background::Image { path: "/tmp/bg.png".to_string() },
+            Background::Image {
+                path: "/tmp/bg.png".to_string(),
+                fit: ImageFit::Tile,
+                blur: 8.0,
+                dim: 0.25,
+            },
+            Background::Transparent,
         ];
 
         for bg in &backgrounds {
@@ -243,4 +715,33 @@ mod tests {
             assert_eq!(json, re_json);
         }
     }
+
+    #[test]
+    fn test_background_image_legacy_json_defaults() {
+        let bg: Background = serde_json::from_str(r#"{"Image":{"path":"/tmp/bg.png"}}"#).unwrap();
+        match bg {
+            Background::Image { path, fit, blur, dim } => {
+                assert_eq!(path, "/tmp/bg.png");
+                assert!(matches!(fit, ImageFit::Cover));
+                assert_eq!(blur, 0.0);
+                assert_eq!(dim, 0.0);
+            }
+            other => panic!("Expected image background, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_background_gradient_legacy_json_defaults() {
+        let json = r##"{"Gradient":{"colors":["#000","#fff"],"angle":90}}"##;
+        match serde_json::from_str::<Background>(json).unwrap() {
+            Background::Gradient { angle, kind, stops, center_x, center_y, dither, .. } => {
+                assert_eq!(angle, 90.0);
+                assert!(matches!(kind, GradientKind::Linear));
+                assert!(stops.is_empty());
+                assert_eq!((center_x, center_y), (0.5, 0.5));
+                assert!(!dither);
+            }
+            other => panic!("Expected gradient background, got {:?}", other),
+        }
+    }
 }
@@ -27,9 +27,42 @@ pub struct MouseEvent {
     pub button: MouseButton,
 }
 
+/// Modifier keys held during a key press
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Modifiers {
+    #[serde(default)]
+    pub command: bool,
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub option: bool,
+    #[serde(default)]
+    pub control: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyEvent {
+    /// Milliseconds since recording start
+    pub timestamp_ms: u64,
+    /// Key name, e.g. "P", "Return" or "Left"
+    pub key: String,
+    #[serde(default)]
+    pub modifiers: Modifiers,
+}
+
+impl KeyEvent {
+    /// A combo with command or control, as opposed to typing. Option and
+    /// shift alone still type characters, such as "@" or "é".
+    pub fn is_shortcut(&self) -> bool {
+        self.modifiers.command || self.modifiers.control
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct RecordedEvents {
     pub mouse_events: Vec<MouseEvent>,
+    #[serde(default)]
+    pub key_events: Vec<KeyEvent>,
     pub recording_start_ms: u64,
     pub display_width: f64,
     pub display_height: f64,
@@ -39,6 +72,7 @@ impl RecordedEvents {
     pub fn new(display_width: f64, display_height: f64) -> Self {
         Self {
             mouse_events: Vec::new(),
+            key_events: Vec::new(),
             recording_start_ms: 0,
             display_width,
             display_height,
@@ -51,6 +85,38 @@ impl RecordedEvents {
             .filter(|e| matches!(e.event_type, EventType::Click))
             .collect()
     }
+
+    /// Map event coordinates from display points into a frame of the given
+    /// pixel size. Retina captures are recorded at a multiple of the display
+    /// size, so events need rescaling before they can address video pixels.
+    pub fn scaled_to(&self, frame_width: f64, frame_height: f64) -> RecordedEvents {
+        let scale_x = if self.display_width > 0.0 {
+            frame_width / self.display_width
+        } else {
+            1.0
+        };
+        let scale_y = if self.display_height > 0.0 {
+            frame_height / self.display_height
+        } else {
+            1.0
+        };
+
+        RecordedEvents {
+            mouse_events: self
+                .mouse_events
+                .iter()
+                .map(|e| MouseEvent {
+                    x: e.x * scale_x,
+                    y: e.y * scale_y,
+                    ..e.clone()
+                })
+                .collect(),
+            key_events: self.key_events.clone(),
+            recording_start_ms: self.recording_start_ms,
+            display_width: frame_width,
+            display_height: frame_height,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -77,6 +143,7 @@ mod tests {
                 make_event(EventType::Click),
                 make_event(EventType::Move),
             ],
+            key_events: Vec::new(),
             recording_start_ms: 0,
             display_width: 1920.0,
             display_height: 1080.0,
@@ -96,6 +163,7 @@ mod tests {
                 make_event(EventType::Move),
                 make_event(EventType::Scroll),
             ],
+            key_events: Vec::new(),
             recording_start_ms: 0,
             display_width: 1920.0,
             display_height: 1080.0,
@@ -103,4 +171,53 @@ mod tests {
 
         assert!(events.click_events().is_empty());
     }
+
+    #[test]
+    fn test_scaled_to_retina_frame() {
+        let mut click = make_event(EventType::Click);
+        click.x = 100.0;
+        click.y = 50.0;
+        let events = RecordedEvents {
+            mouse_events: vec![click],
+            key_events: Vec::new(),
+            recording_start_ms: 0,
+            display_width: 1440.0,
+            display_height: 900.0,
+        };
+
+        let scaled = events.scaled_to(2880.0, 1800.0);
+        assert!((scaled.mouse_events[0].x - 200.0).abs() < 1e-10);
+        assert!((scaled.mouse_events[0].y - 100.0).abs() < 1e-10);
+        assert!((scaled.display_width - 2880.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_key_event_is_shortcut() {
+        let key = |modifiers| KeyEvent { timestamp_ms: 0, key: "P".to_string(), modifiers };
+        assert!(!key(Modifiers::default()).is_shortcut());
+        assert!(!key(Modifiers { shift: true, ..Modifiers::default() }).is_shortcut());
+        assert!(!key(Modifiers { option: true, ..Modifiers::default() }).is_shortcut());
+        assert!(!key(Modifiers { option: true, shift: true, ..Modifiers::default() }).is_shortcut());
+        assert!(key(Modifiers { command: true, option: true, ..Modifiers::default() }).is_shortcut());
+        assert!(key(Modifiers { command: true, shift: true, ..Modifiers::default() }).is_shortcut());
+        assert!(key(Modifiers { control: true, ..Modifiers::default() }).is_shortcut());
+    }
+
+    #[test]
+    fn test_events_without_key_events() {
+        let json = r#"{"mouse_events":[],"recording_start_ms":0,"display_width":1440,"display_height":900}"#;
+        let events: RecordedEvents = serde_json::from_str(json).unwrap();
+        assert!(events.key_events.is_empty());
+    }
+
+    #[test]
+    fn test_scaled_to_unknown_display_size() {
+        let events = RecordedEvents {
+            mouse_events: vec![make_event(EventType::Click)],
+            ..RecordedEvents::default()
+        };
+
+        let scaled = events.scaled_to(1920.0, 1080.0);
+        assert!((scaled.mouse_events[0].x - events.mouse_events[0].x).abs() < 1e-10);
+    }
 }
@@ -1,3 +1,7 @@
+pub mod annotations;
 pub mod effects;
 pub mod events;
+pub mod overlay;
 pub mod project;
+pub mod redactions;
+pub mod settings;
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+use super::effects::Shadow;
+
+/// Corner of the frame the overlay is pinned to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum OverlayPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum OverlayRangeMode {
+    Hidden,
+    Enlarged,
+}
+
+/// A time range during which the overlay is hidden or enlarged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayRange {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub mode: OverlayRangeMode,
+}
+
+/// A second video (e.g. a facecam) drawn picture-in-picture over the recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayTrack {
+    pub path: String,
+    /// Recording time (ms) at which the overlay video starts. Negative
+    /// values skip the start of the overlay video.
+    #[serde(default)]
+    pub offset_ms: i64,
+    #[serde(default)]
+    pub position: OverlayPosition,
+    /// Overlay width as a fraction of the frame width, default 0.22
+    #[serde(default = "default_overlay_size")]
+    pub size: f64,
+    /// Overlay width while enlarged, default 0.45
+    #[serde(default = "default_enlarged_size")]
+    pub enlarged_size: f64,
+    /// Gap to the frame edges in pixels, default 32
+    #[serde(default = "default_overlay_margin")]
+    pub margin: u32,
+    /// Pixels, ignored for circles
+    #[serde(default = "default_overlay_corner_radius")]
+    pub corner_radius: u32,
+    /// Crop to a circle instead of a rounded rectangle
+    #[serde(default)]
+    pub circle: bool,
+    #[serde(default)]
+    pub shadow: Shadow,
+    #[serde(default)]
+    pub ranges: Vec<OverlayRange>,
+}
+
+fn default_overlay_size() -> f64 {
+    0.22
+}
+
+fn default_enlarged_size() -> f64 {
+    0.45
+}
+
+fn default_overlay_margin() -> u32 {
+    32
+}
+
+fn default_overlay_corner_radius() -> u32 {
+    16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay_track_json_defaults() {
+        let json = r#"{ "path": "/tmp/camera.mp4" }"#;
+        let track: OverlayTrack = serde_json::from_str(json).unwrap();
+        assert_eq!(track.offset_ms, 0);
+        assert_eq!(track.position, OverlayPosition::BottomRight);
+        assert!((track.size - 0.22).abs() < 1e-10);
+        assert_eq!(track.margin, 32);
+        assert!(!track.circle);
+        assert!(track.ranges.is_empty());
+    }
+}
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 
-use super::effects::{CursorConfig, ExportConfig, FrameStyle, ZoomConfig};
+use super::annotations::Annotation;
+use super::effects::{ColorGrade, CursorConfig, ExportConfig, FrameStyle, KeystrokeConfig, ZoomConfig};
+use super::overlay::OverlayTrack;
+use super::redactions::Redaction;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
@@ -15,8 +18,29 @@ pub struct Project {
     pub fps: f64,
     pub zoom_config: ZoomConfig,
     pub cursor_config: CursorConfig,
+    #[serde(default)]
+    pub keystroke_config: KeystrokeConfig,
+    #[serde(default)]
+    pub color_grade: ColorGrade,
     pub frame_style: FrameStyle,
     pub export_config: ExportConfig,
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+    /// Regions hidden in every export, e.g. API keys or email addresses
+    #[serde(default)]
+    pub redactions: Vec<Redaction>,
+    /// Picture-in-picture video, e.g. a separately recorded facecam
+    #[serde(default)]
+    pub overlay: Option<OverlayTrack>,
+    /// The recording already shows the system cursor, so exports don't
+    /// draw another one over it. Projects from before this field were all
+    /// recorded with the cursor.
+    #[serde(default = "default_cursor_in_video")]
+    pub cursor_in_video: bool,
+}
+
+fn default_cursor_in_video() -> bool {
+    true
 }
 
 impl Project {
@@ -44,8 +68,14 @@ impl Project {
             fps,
             zoom_config: ZoomConfig::default(),
             cursor_config: CursorConfig::default(),
+            keystroke_config: KeystrokeConfig::default(),
+            color_grade: ColorGrade::default(),
             frame_style: FrameStyle::default(),
             export_config: ExportConfig::default(),
+            annotations: Vec::new(),
+            redactions: Vec::new(),
+            overlay: None,
+            cursor_in_video: false,
         }
     }
 }
@@ -125,4 +155,23 @@ mod tests {
         );
         assert!(project.created_at > 0, "created_at should be a real timestamp");
     }
+
+    #[test]
+    fn test_legacy_project_has_cursor_in_video() {
+        let project = Project::new(
+            "test".to_string(),
+            "Test".to_string(),
+            "".to_string(),
+            "".to_string(),
+            100,
+            100,
+            30.0,
+        );
+        assert!(!project.cursor_in_video);
+
+        let mut json = serde_json::to_value(&project).unwrap();
+        json.as_object_mut().unwrap().remove("cursor_in_video");
+        let legacy: Project = serde_json::from_value(json).unwrap();
+        assert!(legacy.cursor_in_video);
+    }
 }
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+/// How a redacted region is obscured
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RedactionMode {
+    /// Gaussian blur with the given standard deviation in source pixels
+    Blur {
+        #[serde(default = "default_blur_sigma")]
+        sigma: f64,
+    },
+    /// Average the region over square blocks of `block_size` source pixels
+    Pixelate {
+        #[serde(default = "default_block_size")]
+        block_size: u32,
+    },
+    /// Cover the region with an opaque hex color
+    Fill {
+        #[serde(default = "default_fill_color")]
+        color: String,
+    },
+}
+
+impl Default for RedactionMode {
+    fn default() -> Self {
+        Self::Blur { sigma: default_blur_sigma() }
+    }
+}
+
+fn default_blur_sigma() -> f64 {
+    12.0
+}
+
+fn default_block_size() -> u32 {
+    16
+}
+
+fn default_fill_color() -> String {
+    "#000000".to_string()
+}
+
+/// A rectangle of the recording hidden between `start_ms` and `end_ms`, e.g.
+/// an API key or an email address
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Redaction {
+    pub id: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Source video pixels
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    #[serde(default)]
+    pub mode: RedactionMode,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redaction_json_defaults() {
+        let json = r#"{ "id": "r1", "start_ms": 0, "end_ms": 1000, "x": 10, "y": 20, "width": 100, "height": 30 }"#;
+        let redaction: Redaction = serde_json::from_str(json).unwrap();
+        assert_eq!(redaction.mode, RedactionMode::Blur { sigma: 12.0 });
+
+        let json = r#"{ "Pixelate": {} }"#;
+        let mode: RedactionMode = serde_json::from_str(json).unwrap();
+        assert_eq!(mode, RedactionMode::Pixelate { block_size: 16 });
+    }
+}
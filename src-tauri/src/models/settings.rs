@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+use super::effects::Watermark;
+use super::events::KeyEvent;
+use super::project::Project;
+
+/// Which key presses are recorded alongside the screen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum KeyCapture {
+    /// Key presses are only recorded once the user opts in
+    #[default]
+    Off,
+    /// Only combos with command or control, so typed text such as
+    /// passwords never ends up in the events file. Option alone types
+    /// characters, so it doesn't count.
+    ShortcutsOnly,
+    All,
+}
+
+impl KeyCapture {
+    pub fn records(&self, event: &KeyEvent) -> bool {
+        match self {
+            Self::Off => false,
+            Self::ShortcutsOnly => event.is_shortcut(),
+            Self::All => true,
+        }
+    }
+}
+
+/// App-wide preferences, stored outside any project
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppSettings {
+    /// Watermark added to every new project
+    #[serde(default)]
+    pub default_watermark: Option<Watermark>,
+    #[serde(default)]
+    pub key_capture: KeyCapture,
+}
+
+impl AppSettings {
+    /// Apply the defaults to a newly created project
+    pub fn apply_to(&self, project: &mut Project) {
+        if let Some(watermark) = &self.default_watermark {
+            project.frame_style.watermark = Some(watermark.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::effects::WatermarkAnchor;
+    use crate::models::events::Modifiers;
+
+    #[test]
+    fn test_apply_default_watermark() {
+        let mut project = Project::new(
+            "p".to_string(),
+            "P".to_string(),
+            "".to_string(),
+            "".to_string(),
+            100,
+            100,
+            30.0,
+        );
+        AppSettings::default().apply_to(&mut project);
+        assert!(project.frame_style.watermark.is_none());
+
+        let settings: AppSettings =
+            serde_json::from_str(r#"{"default_watermark":{"path":"/tmp/logo.png","anchor":"TopLeft"}}"#).unwrap();
+        settings.apply_to(&mut project);
+        let watermark = project.frame_style.watermark.unwrap();
+        assert_eq!(watermark.path, "/tmp/logo.png");
+        assert_eq!(watermark.anchor, WatermarkAnchor::TopLeft);
+    }
+
+    #[test]
+    fn test_key_capture_privacy() {
+        let typed = KeyEvent { timestamp_ms: 0, key: "A".to_string(), modifiers: Modifiers::default() };
+        let shortcut = KeyEvent {
+            modifiers: Modifiers { command: true, ..Modifiers::default() },
+            ..typed.clone()
+        };
+
+        assert!(!KeyCapture::ShortcutsOnly.records(&typed));
+        let option_typed = KeyEvent {
+            modifiers: Modifiers { option: true, ..Modifiers::default() },
+            ..typed.clone()
+        };
+        assert!(!KeyCapture::ShortcutsOnly.records(&option_typed));
+        assert!(KeyCapture::ShortcutsOnly.records(&shortcut));
+        assert!(KeyCapture::All.records(&typed));
+        assert!(!KeyCapture::Off.records(&shortcut));
+        assert_eq!(AppSettings::default().key_capture, KeyCapture::Off);
+        let settings: AppSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.key_capture, KeyCapture::Off);
+    }
+}
@@ -0,0 +1,420 @@
+use std::collections::HashMap;
+
+use image::{Rgba, RgbaImage};
+
+use crate::models::annotations::{Annotation, AnnotationKind, AnnotationSpace};
+use crate::processing::background::parse_hex_color;
+use crate::processing::layer::{FrameContext, Layer, Surfaces};
+use crate::processing::shape::{self, Shape};
+use crate::processing::text::Font;
+use crate::processing::zoom::FrameViewport;
+
+/// Opacity of highlight boxes, so the content underneath stays readable
+const HIGHLIGHT_OPACITY: f64 = 0.35;
+/// Arrow head length and half-width, in multiples of the line width
+const ARROW_HEAD_LENGTH: f64 = 4.0;
+const ARROW_HEAD_HALF_WIDTH: f64 = 2.5;
+/// Padding around text drawn on a background box, in multiples of the font size
+const TEXT_PADDING_X: f64 = 0.5;
+const TEXT_PADDING_Y: f64 = 0.25;
+
+/// Maps annotation coordinates onto the image being drawn
+#[derive(Debug, Clone, Copy)]
+struct Transform {
+    offset_x: f64,
+    offset_y: f64,
+    scale: f64,
+}
+
+impl Transform {
+    const IDENTITY: Self = Self { offset_x: 0.0, offset_y: 0.0, scale: 1.0 };
+
+    /// Source pixels to the zoomed frame, which shows `viewport` stretched
+    /// over `frame_width` pixels
+    fn for_viewport(viewport: &FrameViewport, frame_width: u32) -> Self {
+        let scale = if viewport.width > 0.0 { frame_width as f64 / viewport.width } else { 1.0 };
+        Self { offset_x: viewport.x, offset_y: viewport.y, scale }
+    }
+
+    fn point(&self, x: f64, y: f64) -> (f64, f64) {
+        ((x - self.offset_x) * self.scale, (y - self.offset_y) * self.scale)
+    }
+
+    fn length(&self, value: f64) -> f64 {
+        value * self.scale
+    }
+}
+
+struct PreparedAnnotation {
+    annotation: Annotation,
+    color: Rgba<u8>,
+    background: Option<Rgba<u8>>,
+    /// Index into `AnnotationLayer::fonts` for text annotations
+    font: Option<usize>,
+}
+
+/// Renders a project's timed annotations in one space. Source-space
+/// annotations are drawn onto the zoomed video frame so they follow the
+/// zoom; canvas-space ones are drawn onto the finished canvas.
+pub struct AnnotationLayer {
+    space: AnnotationSpace,
+    annotations: Vec<PreparedAnnotation>,
+    fonts: Vec<Font>,
+}
+
+impl AnnotationLayer {
+    /// Keep the annotations in `space`, parsing colors and loading every
+    /// font they need up front
+    pub fn new(annotations: &[Annotation], space: AnnotationSpace) -> Result<Self, String> {
+        let mut fonts = Vec::new();
+        let mut font_indices: HashMap<Option<String>, usize> = HashMap::new();
+        let mut prepared = Vec::with_capacity(annotations.len());
+
+        for annotation in annotations {
+            if annotation.space != space || annotation.end_ms <= annotation.start_ms {
+                continue;
+            }
+            let (font, background) = match &annotation.kind {
+                AnnotationKind::Text { font_path, background, .. } => {
+                    let index = match font_indices.get(font_path) {
+                        Some(&index) => index,
+                        None => {
+                            let font = match font_path {
+                                Some(path) => Font::load(path)?,
+                                None => Font::system(),
+                            };
+                            fonts.push(font);
+                            font_indices.insert(font_path.clone(), fonts.len() - 1);
+                            fonts.len() - 1
+                        }
+                    };
+                    (Some(index), background.as_deref().map(parse_hex_color))
+                }
+                _ => (None, None),
+            };
+            prepared.push(PreparedAnnotation {
+                annotation: annotation.clone(),
+                color: parse_hex_color(&annotation.color),
+                background,
+                font,
+            });
+        }
+
+        Ok(Self { space, annotations: prepared, fonts })
+    }
+
+    /// Draw source-space annotations onto a frame showing `viewport`
+    pub fn draw_source(&self, frame: &mut RgbaImage, time_ms: u64, viewport: &FrameViewport) {
+        let transform = Transform::for_viewport(viewport, frame.width());
+        self.draw(frame, time_ms, AnnotationSpace::Source, transform);
+    }
+
+    /// Draw canvas-space annotations onto the output canvas
+    pub fn draw_canvas(&self, canvas: &mut RgbaImage, time_ms: u64) {
+        self.draw(canvas, time_ms, AnnotationSpace::Canvas, Transform::IDENTITY);
+    }
+
+    fn draw(&self, image: &mut RgbaImage, time_ms: u64, space: AnnotationSpace, transform: Transform) {
+        for prepared in &self.annotations {
+            let annotation = &prepared.annotation;
+            if annotation.space != space {
+                continue;
+            }
+            let opacity = annotation.opacity.clamp(0.0, 1.0) * annotation.fade_at(time_ms as f64);
+            if opacity <= 0.0 {
+                continue;
+            }
+            self.draw_annotation(image, prepared, opacity, transform);
+        }
+    }
+
+    fn draw_annotation(&self, image: &mut RgbaImage, prepared: &PreparedAnnotation, opacity: f64, t: Transform) {
+        let annotation = &prepared.annotation;
+        let color = with_opacity(prepared.color, opacity);
+        let (x, y) = t.point(annotation.x, annotation.y);
+
+        match &annotation.kind {
+            AnnotationKind::Text { text, size, .. } => {
+                let Some(font) = prepared.font.map(|index| &self.fonts[index]) else {
+                    return;
+                };
+                let size = t.length(*size);
+                let background = prepared.background.map(|bg| with_opacity(bg, opacity));
+                draw_text_block(image, font, text, x, y, size, color, background);
+            }
+            AnnotationKind::Arrow { to_x, to_y, width } => {
+                let to = t.point(*to_x, *to_y);
+                draw_arrow(image, (x, y), to, t.length(*width), color);
+            }
+            AnnotationKind::Rectangle { width, height, stroke_width, corner_radius, fill } => {
+                let rect = Shape::rounded(x, y, t.length(*width), t.length(*height), t.length(*corner_radius));
+                if let Some(fill) = fill {
+                    rect.fill(image, with_opacity(parse_hex_color(fill), opacity));
+                }
+                rect.stroke(image, color, t.length(*stroke_width));
+            }
+            AnnotationKind::Highlight { width, height, corner_radius } => {
+                let rect = Shape::rounded(x, y, t.length(*width), t.length(*height), t.length(*corner_radius));
+                rect.fill(image, with_opacity(color, HIGHLIGHT_OPACITY));
+            }
+        }
+    }
+}
+
+impl Layer for AnnotationLayer {
+    fn render(&self, surfaces: &mut Surfaces, ctx: &FrameContext) {
+        match self.space {
+            AnnotationSpace::Source => self.draw_source(&mut surfaces.video, ctx.time_ms, &ctx.viewport),
+            AnnotationSpace::Canvas => self.draw_canvas(&mut surfaces.canvas, ctx.time_ms),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_text_block(
+    image: &mut RgbaImage,
+    font: &Font,
+    text: &str,
+    x: f64,
+    y: f64,
+    size: f64,
+    color: Rgba<u8>,
+    background: Option<Rgba<u8>>,
+) {
+    if size <= 0.0 {
+        return;
+    }
+    let line_height = font.line_height(size);
+    let lines: Vec<&str> = text.lines().collect();
+
+    let (mut text_x, mut text_y) = (x, y);
+    if let Some(background) = background {
+        let (pad_x, pad_y) = (size * TEXT_PADDING_X, size * TEXT_PADDING_Y);
+        let width = lines.iter().map(|line| font.text_width(line, size)).fold(0.0, f64::max);
+        let height = line_height * lines.len() as f64;
+        Shape::rounded(x, y, width + pad_x * 2.0, height + pad_y * 2.0, size * 0.3).fill(image, background);
+        text_x += pad_x;
+        text_y += pad_y;
+    }
+
+    for (i, line) in lines.iter().enumerate() {
+        font.draw_text(image, line, text_x, text_y + line_height * i as f64, size, color);
+    }
+}
+
+/// Draw a line ending in a filled triangular head at `to`
+fn draw_arrow(image: &mut RgbaImage, from: (f64, f64), to: (f64, f64), width: f64, color: Rgba<u8>) {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length <= 0.0 || width <= 0.0 {
+        return;
+    }
+    let (ux, uy) = (dx / length, dy / length);
+    let head_length = (width * ARROW_HEAD_LENGTH).min(length);
+    let head_half_width = head_length / ARROW_HEAD_LENGTH * ARROW_HEAD_HALF_WIDTH;
+    let base = (to.0 - ux * head_length, to.1 - uy * head_length);
+
+    // The shaft stops inside the head so its round cap doesn't poke out of the tip
+    let shaft_end = (base.0 + ux * head_length * 0.5, base.1 + uy * head_length * 0.5);
+    if head_length < length {
+        shape::draw_line(image, from, shaft_end, width, color);
+    }
+    shape::fill_polygon(
+        image,
+        &[
+            to,
+            (base.0 - uy * head_half_width, base.1 + ux * head_half_width),
+            (base.0 + uy * head_half_width, base.1 - ux * head_half_width),
+        ],
+        color,
+    );
+}
+
+fn with_opacity(color: Rgba<u8>, opacity: f64) -> Rgba<u8> {
+    Rgba([color[0], color[1], color[2], (color[3] as f64 * opacity).round() as u8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+    fn annotation(kind: AnnotationKind, x: f64, y: f64, space: AnnotationSpace) -> Annotation {
+        Annotation {
+            id: "a".to_string(),
+            kind,
+            start_ms: 1000,
+            end_ms: 2000,
+            x,
+            y,
+            space,
+            color: "#FF0000".to_string(),
+            opacity: 1.0,
+            fade_in_ms: 0,
+            fade_out_ms: 0,
+        }
+    }
+
+    fn rectangle(x: f64, y: f64, space: AnnotationSpace) -> Annotation {
+        annotation(
+            AnnotationKind::Rectangle {
+                width: 20.0,
+                height: 20.0,
+                stroke_width: 2.0,
+                corner_radius: 0.0,
+                fill: Some("#00FF00".to_string()),
+            },
+            x,
+            y,
+            space,
+        )
+    }
+
+    fn viewport(x: f64, y: f64, size: f64, zoom: f64) -> FrameViewport {
+        FrameViewport {
+            x,
+            y,
+            width: size,
+            height: size,
+            zoom,
+            center_x: x + size / 2.0,
+            center_y: y + size / 2.0,
+        }
+    }
+
+    #[test]
+    fn test_draws_only_while_active() {
+        let layer = AnnotationLayer::new(&[rectangle(10.0, 10.0, AnnotationSpace::Source)], AnnotationSpace::Source).unwrap();
+        let viewport = viewport(0.0, 0.0, 100.0, 1.0);
+
+        let mut before = RgbaImage::from_pixel(100, 100, BLACK);
+        layer.draw_source(&mut before, 999, &viewport);
+        assert_eq!(*before.get_pixel(20, 20), BLACK);
+
+        let mut during = RgbaImage::from_pixel(100, 100, BLACK);
+        layer.draw_source(&mut during, 1500, &viewport);
+        assert_eq!(*during.get_pixel(20, 20), Rgba([0, 255, 0, 255]));
+        assert_eq!(*during.get_pixel(10, 20), Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_source_annotation_follows_zoom() {
+        let layer = AnnotationLayer::new(&[rectangle(50.0, 50.0, AnnotationSpace::Source)], AnnotationSpace::Source).unwrap();
+        // 2x zoom on the bottom-right quarter
+        let viewport = viewport(50.0, 50.0, 50.0, 2.0);
+        let mut frame = RgbaImage::from_pixel(100, 100, BLACK);
+        layer.draw_source(&mut frame, 1500, &viewport);
+
+        // The 20px box now starts at the frame origin and spans 40px
+        assert_eq!(*frame.get_pixel(20, 20), Rgba([0, 255, 0, 255]));
+        assert_eq!(*frame.get_pixel(35, 35), Rgba([0, 255, 0, 255]));
+        assert_eq!(*frame.get_pixel(45, 45), BLACK);
+    }
+
+    #[test]
+    fn test_layer_keeps_only_its_space() {
+        let annotations = [rectangle(10.0, 10.0, AnnotationSpace::Canvas)];
+        let source = AnnotationLayer::new(&annotations, AnnotationSpace::Source).unwrap();
+        let canvas_layer = AnnotationLayer::new(&annotations, AnnotationSpace::Canvas).unwrap();
+
+        let mut frame = RgbaImage::from_pixel(100, 100, BLACK);
+        source.draw_source(&mut frame, 1500, &viewport(0.0, 0.0, 100.0, 1.0));
+        assert_eq!(*frame.get_pixel(20, 20), BLACK);
+
+        let mut canvas = RgbaImage::from_pixel(100, 100, BLACK);
+        canvas_layer.draw_canvas(&mut canvas, 1500);
+        assert_eq!(*canvas.get_pixel(20, 20), Rgba([0, 255, 0, 255]));
+    }
+
+    #[test]
+    fn test_fade_scales_opacity() {
+        let mut faded = rectangle(10.0, 10.0, AnnotationSpace::Canvas);
+        faded.fade_in_ms = 200;
+        let layer = AnnotationLayer::new(&[faded], AnnotationSpace::Canvas).unwrap();
+
+        let mut canvas = RgbaImage::from_pixel(100, 100, BLACK);
+        layer.draw_canvas(&mut canvas, 1100);
+        let green = canvas.get_pixel(20, 20)[1];
+        // Half opacity in linear light
+        assert!((184..=192).contains(&green), "half faded, got {}", green);
+    }
+
+    #[test]
+    fn test_arrow_reaches_tip() {
+        let arrow = annotation(
+            AnnotationKind::Arrow { to_x: 80.0, to_y: 50.0, width: 4.0 },
+            10.0,
+            50.0,
+            AnnotationSpace::Canvas,
+        );
+        let layer = AnnotationLayer::new(&[arrow], AnnotationSpace::Canvas).unwrap();
+        let mut canvas = RgbaImage::from_pixel(100, 100, BLACK);
+        layer.draw_canvas(&mut canvas, 1500);
+
+        assert_eq!(canvas.get_pixel(40, 50)[0], 255);
+        assert_eq!(canvas.get_pixel(40, 44)[0], 0);
+        // The head is wider than the shaft
+        assert_eq!(canvas.get_pixel(68, 44)[0], 255);
+        assert_eq!(canvas.get_pixel(85, 50)[0], 0);
+    }
+
+    #[test]
+    fn test_highlight_is_translucent() {
+        let highlight = annotation(
+            AnnotationKind::Highlight { width: 30.0, height: 10.0, corner_radius: 0.0 },
+            10.0,
+            10.0,
+            AnnotationSpace::Canvas,
+        );
+        let layer = AnnotationLayer::new(&[highlight], AnnotationSpace::Canvas).unwrap();
+        let mut canvas = RgbaImage::from_pixel(100, 100, BLACK);
+        layer.draw_canvas(&mut canvas, 1500);
+        let red = canvas.get_pixel(20, 15)[0];
+        assert!(red > 130 && red < 190, "got {}", red);
+    }
+
+    #[test]
+    fn test_missing_font_errors() {
+        let text = annotation(
+            AnnotationKind::Text {
+                text: "Hi".to_string(),
+                size: 20.0,
+                font_path: Some("/nonexistent/font.ttf".to_string()),
+                background: None,
+            },
+            0.0,
+            0.0,
+            AnnotationSpace::Canvas,
+        );
+        assert!(AnnotationLayer::new(&[text], AnnotationSpace::Canvas).is_err());
+    }
+
+    #[test]
+    fn test_text_with_background() {
+        let text = annotation(
+            AnnotationKind::Text {
+                text: "Hello\nworld".to_string(),
+                size: 20.0,
+                font_path: None,
+                background: Some("#FFFFFF".to_string()),
+            },
+            10.0,
+            10.0,
+            AnnotationSpace::Canvas,
+        );
+        let layer = AnnotationLayer::new(&[text], AnnotationSpace::Canvas).unwrap();
+        let mut canvas = RgbaImage::from_pixel(200, 100, BLACK);
+        layer.draw_canvas(&mut canvas, 1500);
+
+        // Padding corner is the background color, and both lines drew red text
+        assert_eq!(*canvas.get_pixel(13, 13), Rgba([255, 255, 255, 255]));
+        let line_height = Font::system().line_height(20.0);
+        let red_rows = |y0: f64| {
+            (y0 as u32..(y0 + line_height) as u32)
+                .any(|y| (0..200).any(|x| *canvas.get_pixel(x, y) == Rgba([255, 0, 0, 255])))
+        };
+        assert!(red_rows(15.0));
+        assert!(red_rows(15.0 + line_height));
+    }
+}
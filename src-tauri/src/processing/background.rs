@@ -1,13 +1,30 @@
+use std::path::Path;
+
+use image::imageops::{self, FilterType};
 use image::{Rgba, RgbaImage};
 
-use crate::models::effects::{Background, FrameStyle, Shadow};
+use crate::models::effects::{Background, Border, CornerStyle, FrameStyle, ImageFit, Shadow};
+use crate::processing::blend::alpha_blend;
+use crate::processing::chrome::{self, ChromeInsets};
+use crate::processing::gradient::{draw_gradient, ColorRamp};
+use crate::processing::layer::{FrameContext, Layer, Surfaces};
+
+/// Where the video frame sits on the canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
 
-/// Render the background canvas with the styled frame
+/// Render the background canvas with the styled frame.
+/// Fails if an image background cannot be loaded or gradient stops are invalid.
 pub fn render_background(
     canvas_width: u32,
     canvas_height: u32,
     style: &FrameStyle,
-) -> RgbaImage {
+) -> Result<RgbaImage, String> {
     let mut canvas = RgbaImage::new(canvas_width, canvas_height);
 
     // Draw background
@@ -18,237 +35,566 @@ pub fn render_background(
                 *pixel = rgba;
             }
         }
-        Background::Gradient { colors, angle } => {
-            draw_gradient(&mut canvas, colors, *angle);
+        Background::Gradient { colors, angle, kind, stops, center_x, center_y, dither } => {
+            // No colors leaves the canvas transparent
+            if !colors.is_empty() {
+                let ramp = ColorRamp::new(colors, stops)?;
+                draw_gradient(&mut canvas, &ramp, kind, *angle, (*center_x, *center_y), *dither);
+            }
         }
-        Background::Image { path: _ } => {
-            // TODO: load and tile/stretch background image
-            let rgba = parse_hex_color("#1a1a2e");
-            for pixel in canvas.pixels_mut() {
-                *pixel = rgba;
+        Background::Image { path, fit, blur, dim } => {
+            let image = load_image(path)?;
+            canvas = fit_image(&image, canvas_width, canvas_height, fit);
+            if *blur > 0.0 {
+                canvas = imageops::blur(&canvas, *blur as f32);
+            }
+            if *dim > 0.0 {
+                dim_image(&mut canvas, *dim);
             }
         }
+        // The canvas starts out fully transparent
+        Background::Transparent => {}
     }
 
-    canvas
+    Ok(canvas)
 }
 
-/// Composite the video frame onto the background with rounded corners and shadow
-pub fn composite_frame(
-    background: &RgbaImage,
-    video_frame: &RgbaImage,
-    style: &FrameStyle,
-) -> RgbaImage {
-    let mut canvas = background.clone();
-    let canvas_width = canvas.width();
-    let canvas_height = canvas.height();
-
-    let frame_width = video_frame.width();
-    let frame_height = video_frame.height();
-
-    // Calculate position (centered with padding)
-    let offset_x = (canvas_width.saturating_sub(frame_width)) / 2;
-    let offset_y = (canvas_height.saturating_sub(frame_height)) / 2;
-
-    // Draw shadow
-    draw_shadow(&mut canvas, offset_x, offset_y, frame_width, frame_height, &style.shadow);
-
-    // Draw video frame with rounded corners
-    draw_rounded_frame(
-        &mut canvas,
-        video_frame,
-        offset_x,
-        offset_y,
-        style.corner_radius,
-    );
+/// Corner geometry of the frame
+#[derive(Debug, Clone, Copy)]
+pub struct Corners {
+    pub radius: f64,
+    /// Superellipse exponent: 2 for circular arcs, higher for squircles
+    pub exponent: f64,
+}
+
+impl Corners {
+    pub fn new(radius: u32, style: &CornerStyle) -> Self {
+        let exponent = match style {
+            CornerStyle::Circular => 2.0,
+            CornerStyle::Squircle => 4.0,
+        };
+        Self { radius: radius as f64, exponent }
+    }
+
+    pub fn from_style(style: &FrameStyle) -> Self {
+        Self::new(style.corner_radius, &style.corner_style)
+    }
 
-    canvas
+    /// The same corners grown outward by `amount` pixels
+    fn expanded(self, amount: f64) -> Self {
+        Self { radius: (self.radius + amount).max(0.0), ..self }
+    }
 }
 
-/// Calculate the canvas size needed for the given frame + padding
-pub fn calculate_canvas_size(frame_width: u32, frame_height: u32, style: &FrameStyle) -> (u32, u32) {
-    let canvas_width = frame_width + style.padding * 2;
-    let canvas_height = frame_height + style.padding * 2;
-    (canvas_width, canvas_height)
+/// Coverage of a frame row: a fully covered span plus the anti-aliased
+/// pixels along the rounded corners
+#[derive(Debug, Clone, Default, PartialEq)]
+struct RowCoverage {
+    /// Fully covered `[start, end)` columns
+    solid: (u32, u32),
+    /// Partially covered columns with their coverage (0-255)
+    edges: Vec<(u32, u8)>,
 }
 
-fn draw_gradient(canvas: &mut RgbaImage, colors: &[String], angle_deg: f64) {
-    if colors.is_empty() {
-        return;
-    }
-    if colors.len() == 1 {
-        let rgba = parse_hex_color(&colors[0]);
-        for pixel in canvas.pixels_mut() {
-            *pixel = rgba;
+impl RowCoverage {
+    /// Re-express the row for a span starting `offset` columns in and
+    /// `width` wide, dropping everything outside it
+    fn shifted(self, offset: u32, width: u32) -> Self {
+        if offset == 0 && self.solid.1 <= width {
+            return self;
+        }
+        let clip = |x: u32| x.saturating_sub(offset).min(width);
+        Self {
+            solid: (clip(self.solid.0), clip(self.solid.1)),
+            edges: self
+                .edges
+                .into_iter()
+                .filter(|&(x, _)| x >= offset && x - offset < width)
+                .map(|(x, coverage)| (x - offset, coverage))
+                .collect(),
         }
-        return;
     }
+}
 
-    let width = canvas.width() as f64;
-    let height = canvas.height() as f64;
-    let angle_rad = angle_deg.to_radians();
+/// The parts of the canvas that stay fixed for a whole export: background,
+/// shadow and frame placement. Built once, after which each frame only
+/// rewrites the video region.
+pub struct StaticLayer {
+    canvas: RgbaImage,
+    rect: FrameRect,
+    /// Which pixels of each frame row are visible inside the rounded corners
+    rows: Vec<RowCoverage>,
+}
 
-    // Direction vector
-    let dx = angle_rad.cos();
-    let dy = angle_rad.sin();
+impl StaticLayer {
+    pub fn new(
+        canvas_width: u32,
+        canvas_height: u32,
+        frame_width: u32,
+        frame_height: u32,
+        style: &FrameStyle,
+    ) -> Result<Self, String> {
+        let mut canvas = render_background(canvas_width, canvas_height, style)?;
+        let rect = calculate_frame_rect(canvas_width, canvas_height, frame_width, frame_height, style);
 
-    // Project corners onto the gradient line to find min/max
-    let corners = [(0.0, 0.0), (width, 0.0), (0.0, height), (width, height)];
-    let projections: Vec<f64> = corners.iter().map(|(x, y)| x * dx + y * dy).collect();
-    let min_proj = projections.iter().cloned().fold(f64::INFINITY, f64::min);
-    let max_proj = projections.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-    let range = max_proj - min_proj;
+        // Corners, shadow and border belong to the chrome when there is one
+        let body = chrome::body_rect(&style.chrome, &rect);
+        let corners = Corners::from_style(style);
 
-    let parsed_colors: Vec<Rgba<u8>> = colors.iter().map(|c| parse_hex_color(c)).collect();
+        ShadowMask::new(&body, corners, &style.shadow).draw(&mut canvas);
+        if let Some(border) = &style.border {
+            draw_border(&mut canvas, &body, corners, border);
+        }
+        chrome::draw_chrome(&mut canvas, &style.chrome, &rect, corners, &style.shadow);
 
-    for y in 0..canvas.height() {
-        for x in 0..canvas.width() {
-            let proj = (x as f64) * dx + (y as f64) * dy;
-            let t = ((proj - min_proj) / range).clamp(0.0, 1.0);
+        // The video is clipped by the body's corners wherever it reaches them
+        let (dx, dy) = (rect.x - body.x, rect.y - body.y);
+        let rows = (0..rect.height)
+            .map(|y| row_coverage(y + dy, body.width, body.height, corners).shifted(dx, rect.width))
+            .collect();
 
-            // Interpolate between gradient stops
-            let segment_count = parsed_colors.len() - 1;
-            let segment_t = t * segment_count as f64;
-            let segment_idx = (segment_t as usize).min(segment_count - 1);
-            let local_t = segment_t - segment_idx as f64;
+        Ok(Self { canvas, rect, rows })
+    }
 
-            let c1 = &parsed_colors[segment_idx];
-            let c2 = &parsed_colors[segment_idx + 1];
+    /// Composite the video frame onto a copy of the static layer
+    pub fn compose(&self, video_frame: &RgbaImage) -> RgbaImage {
+        let rect = self.rect;
 
-            let pixel = Rgba([
-                lerp_u8(c1[0], c2[0], local_t),
-                lerp_u8(c1[1], c2[1], local_t),
-                lerp_u8(c1[2], c2[2], local_t),
-                255,
-            ]);
+        // Only resample if the layer was not sized for this frame
+        let resized;
+        let frame = if (rect.width, rect.height) == video_frame.dimensions() {
+            video_frame
+        } else {
+            resized = imageops::resize(video_frame, rect.width, rect.height, FilterType::Triangle);
+            &resized
+        };
 
-            canvas.put_pixel(x, y, pixel);
-        }
-    }
-}
+        let mut canvas = self.canvas.clone();
+        let dst_stride = canvas.width() as usize * 4;
+        let src_stride = rect.width as usize * 4;
+        let src = frame.as_raw();
+        let dst: &mut [u8] = &mut canvas;
 
-fn draw_shadow(
-    canvas: &mut RgbaImage,
-    offset_x: u32,
-    offset_y: u32,
-    width: u32,
-    height: u32,
-    shadow: &Shadow,
-) {
-    let shadow_color = parse_hex_color(&shadow.color);
-    let blur = shadow.blur as i32;
-    let sx = shadow.offset_x as i32;
-    let sy = shadow.offset_y as i32;
-
-    // Simple box shadow approximation
-    let shadow_alpha = (shadow.opacity * 255.0) as u8;
-
-    for dy in -blur..=(height as i32 + blur) {
-        for dx in -blur..=(width as i32 + blur) {
-            let px = (offset_x as i32 + dx + sx) as u32;
-            let py = (offset_y as i32 + dy + sy) as u32;
-
-            if px >= canvas.width() || py >= canvas.height() {
+        for (y, row) in self.rows.iter().enumerate() {
+            let (start, end) = row.solid;
+            if start >= end {
                 continue;
             }
+            let dst_start = (rect.y as usize + y) * dst_stride + (rect.x + start) as usize * 4;
+            let src_start = y * src_stride + start as usize * 4;
+            let len = (end - start) as usize * 4;
+            dst[dst_start..dst_start + len].copy_from_slice(&src[src_start..src_start + len]);
+        }
 
-            // Distance from the rectangle edge
-            let dist_x = if dx < 0 {
-                (-dx) as f64
-            } else if dx >= width as i32 {
-                (dx - width as i32 + 1) as f64
-            } else {
-                0.0
-            };
-            let dist_y = if dy < 0 {
-                (-dy) as f64
-            } else if dy >= height as i32 {
-                (dy - height as i32 + 1) as f64
-            } else {
-                0.0
-            };
-            let dist = (dist_x * dist_x + dist_y * dist_y).sqrt();
-
-            if dist > blur as f64 {
-                continue;
+        // Blend the anti-aliased corner pixels over the background
+        for (y, row) in self.rows.iter().enumerate() {
+            for &(x, coverage) in &row.edges {
+                let src = frame.get_pixel(x, y as u32);
+                let alpha = (src[3] as u32 * coverage as u32 / 255) as u8;
+                let (px, py) = (rect.x + x, rect.y + y as u32);
+                let blended = alpha_blend(canvas.get_pixel(px, py), &Rgba([src[0], src[1], src[2], alpha]));
+                canvas.put_pixel(px, py, blended);
             }
+        }
+
+        canvas
+    }
+}
+
+impl Layer for StaticLayer {
+    fn render(&self, surfaces: &mut Surfaces, _ctx: &FrameContext) {
+        surfaces.canvas = self.compose(&surfaces.video);
+    }
+}
+
+/// Calculate the canvas size needed for the given frame + chrome + padding.
+/// With a fixed aspect ratio the canvas grows along one axis so the frame
+/// keeps its native resolution and is centered in the extra space.
+pub fn calculate_canvas_size(frame_width: u32, frame_height: u32, style: &FrameStyle) -> (u32, u32) {
+    let insets = ChromeInsets::new(&style.chrome, frame_width);
+    let content_width = frame_width + insets.horizontal() + style.padding * 2;
+    let content_height = frame_height + insets.vertical() + style.padding * 2;
+
+    let ratio = match style.aspect_ratio.ratio() {
+        Some(ratio) => ratio,
+        None => return (content_width, content_height),
+    };
+
+    if content_width as f64 / content_height as f64 > ratio {
+        let height = (content_width as f64 / ratio).round() as u32;
+        (content_width, height.max(content_height))
+    } else {
+        let width = (content_height as f64 * ratio).round() as u32;
+        (width.max(content_width), content_height)
+    }
+}
+
+/// Fit the frame and its chrome inside the padded area of the canvas,
+/// preserving the frame's aspect ratio, and center them. Returns where the
+/// video itself goes.
+pub fn calculate_frame_rect(
+    canvas_width: u32,
+    canvas_height: u32,
+    frame_width: u32,
+    frame_height: u32,
+    style: &FrameStyle,
+) -> FrameRect {
+    let area_width = canvas_width.saturating_sub(style.padding * 2).max(1);
+    let area_height = canvas_height.saturating_sub(style.padding * 2).max(1);
+
+    let native = ChromeInsets::new(&style.chrome, frame_width);
+    let outer_width = frame_width + native.horizontal();
+    let outer_height = frame_height + native.vertical();
 
-            let alpha = shadow_alpha as f64 * (1.0 - dist / blur as f64);
-            let alpha = alpha.clamp(0.0, 255.0) as u8;
+    let (width, height) = if outer_width <= area_width && outer_height <= area_height {
+        (frame_width, frame_height)
+    } else {
+        // Chrome scales with the frame, so scaling the whole box scales both
+        let scale = (area_width as f64 / outer_width.max(1) as f64)
+            .min(area_height as f64 / outer_height.max(1) as f64);
+        (
+            ((frame_width as f64 * scale).round() as u32).clamp(1, area_width),
+            ((frame_height as f64 * scale).round() as u32).clamp(1, area_height),
+        )
+    };
+
+    let insets = ChromeInsets::new(&style.chrome, width);
+    let outer_width = width + insets.horizontal();
+    let outer_height = height + insets.vertical();
+    FrameRect {
+        x: (canvas_width.saturating_sub(outer_width)) / 2 + insets.left,
+        y: (canvas_height.saturating_sub(outer_height)) / 2 + insets.top,
+        width,
+        height,
+    }
+}
 
-            // Alpha blend shadow onto canvas
-            let existing = canvas.get_pixel(px, py);
-            let blended = alpha_blend(
-                existing,
-                &Rgba([shadow_color[0], shadow_color[1], shadow_color[2], alpha]),
+fn load_image(path: &str) -> Result<RgbaImage, String> {
+    if !Path::new(path).is_file() {
+        return Err(format!("Background image not found: {}", path));
+    }
+    let image = image::open(path)
+        .map_err(|e| format!("Failed to load background image {}: {}", path, e))?;
+    Ok(image.to_rgba8())
+}
+
+/// Map an image onto a canvas of the given size
+fn fit_image(image: &RgbaImage, width: u32, height: u32, fit: &ImageFit) -> RgbaImage {
+    let (iw, ih) = image.dimensions();
+    if iw == 0 || ih == 0 {
+        return RgbaImage::new(width, height);
+    }
+
+    match fit {
+        ImageFit::Stretch => imageops::resize(image, width, height, FilterType::Lanczos3),
+        ImageFit::Cover => {
+            let scale = (width as f64 / iw as f64).max(height as f64 / ih as f64);
+            let sw = ((iw as f64 * scale).ceil() as u32).max(width);
+            let sh = ((ih as f64 * scale).ceil() as u32).max(height);
+            let scaled = imageops::resize(image, sw, sh, FilterType::Lanczos3);
+            imageops::crop_imm(&scaled, (sw - width) / 2, (sh - height) / 2, width, height)
+                .to_image()
+        }
+        ImageFit::Contain => {
+            let scale = (width as f64 / iw as f64).min(height as f64 / ih as f64);
+            let sw = ((iw as f64 * scale).round() as u32).clamp(1, width);
+            let sh = ((ih as f64 * scale).round() as u32).clamp(1, height);
+            let scaled = imageops::resize(image, sw, sh, FilterType::Lanczos3);
+            let mut canvas = RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 255]));
+            imageops::overlay(
+                &mut canvas,
+                &scaled,
+                ((width - sw) / 2) as i64,
+                ((height - sh) / 2) as i64,
             );
-            canvas.put_pixel(px, py, blended);
+            canvas
         }
+        ImageFit::Tile => RgbaImage::from_fn(width, height, |x, y| *image.get_pixel(x % iw, y % ih)),
     }
 }
 
-fn draw_rounded_frame(
-    canvas: &mut RgbaImage,
-    frame: &RgbaImage,
-    offset_x: u32,
-    offset_y: u32,
-    radius: u32,
-) {
-    let fw = frame.width();
-    let fh = frame.height();
-
-    for y in 0..fh {
-        for x in 0..fw {
-            // Check if pixel is within rounded corners
-            if !is_in_rounded_rect(x, y, fw, fh, radius) {
+/// Darken an image by the given amount (0.0 - 1.0)
+fn dim_image(image: &mut RgbaImage, amount: f64) {
+    let factor = 1.0 - amount.clamp(0.0, 1.0);
+    for pixel in image.pixels_mut() {
+        for c in 0..3 {
+            pixel[c] = (pixel[c] as f64 * factor).round() as u8;
+        }
+    }
+}
+
+/// A pre-blurred drop shadow for the frame.
+///
+/// The blur is the expensive part, and the frame never moves during an
+/// export, so the mask is computed once and blended onto every frame.
+pub struct ShadowMask {
+    /// Canvas position of the mask's top-left pixel (may be off-canvas)
+    left: i64,
+    top: i64,
+    width: usize,
+    height: usize,
+    /// Shadow opacity per pixel, 0-255
+    alpha: Vec<u8>,
+    color: Rgba<u8>,
+}
+
+impl ShadowMask {
+    pub fn new(rect: &FrameRect, corners: Corners, shadow: &Shadow) -> Self {
+        let color = parse_hex_color(&shadow.color);
+        // Match CSS box-shadow, where blur is twice the Gaussian sigma
+        let sigma = (shadow.blur / 2.0).max(0.0);
+        let spread = shadow.spread;
+
+        let shape_width = rect.width as f64 + spread * 2.0;
+        let shape_height = rect.height as f64 + spread * 2.0;
+        let strength = shadow.opacity.clamp(0.0, 1.0) * color[3] as f64 / 255.0;
+        if shape_width <= 0.0 || shape_height <= 0.0 || strength <= 0.0 {
+            return Self::empty(color);
+        }
+        let corners = corners.expanded(spread);
+
+        // Leave room for the blur to fall off to zero
+        let margin = (sigma * 3.0).ceil() as i64 + 1;
+        let shape_left = rect.x as f64 + shadow.offset_x - spread;
+        let shape_top = rect.y as f64 + shadow.offset_y - spread;
+        let left = shape_left.floor() as i64 - margin;
+        let top = shape_top.floor() as i64 - margin;
+        let width = (shape_width.ceil() as i64 + margin * 2 + 1) as usize;
+        let height = (shape_height.ceil() as i64 + margin * 2 + 1) as usize;
+
+        // Anti-aliased coverage of the (spread) rounded rectangle
+        let origin_x = shape_left - left as f64;
+        let origin_y = shape_top - top as f64;
+        let mut coverage = vec![0f32; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                let px = x as f64 + 0.5 - origin_x;
+                let py = y as f64 + 0.5 - origin_y;
+                let dist = rounded_rect_sdf(px, py, shape_width, shape_height, corners);
+                coverage[y * width + x] = (0.5 - dist).clamp(0.0, 1.0) as f32;
+            }
+        }
+
+        if sigma >= 0.5 {
+            gaussian_blur(&mut coverage, width, height, sigma);
+        }
+
+        let alpha = coverage
+            .iter()
+            .map(|c| (*c as f64 * strength * 255.0).round().clamp(0.0, 255.0) as u8)
+            .collect();
+
+        Self { left, top, width, height, alpha, color }
+    }
+
+    fn empty(color: Rgba<u8>) -> Self {
+        Self { left: 0, top: 0, width: 0, height: 0, alpha: Vec::new(), color }
+    }
+
+    /// Blend the shadow onto the canvas
+    pub fn draw(&self, canvas: &mut RgbaImage) {
+        let (cw, ch) = (canvas.width() as i64, canvas.height() as i64);
+        for y in 0..self.height {
+            let py = self.top + y as i64;
+            if py < 0 || py >= ch {
                 continue;
             }
+            for x in 0..self.width {
+                let px = self.left + x as i64;
+                let a = self.alpha[y * self.width + x];
+                if a == 0 || px < 0 || px >= cw {
+                    continue;
+                }
+                let fg = Rgba([self.color[0], self.color[1], self.color[2], a]);
+                let blended = alpha_blend(canvas.get_pixel(px as u32, py as u32), &fg);
+                canvas.put_pixel(px as u32, py as u32, blended);
+            }
+        }
+    }
+}
+
+/// Signed distance from a point to a rounded rectangle spanning
+/// (0, 0)-(width, height); negative inside.
+///
+/// The radius is clamped to half the shorter side. Squircle corners use the
+/// superellipse norm, which is close enough to a true distance for
+/// anti-aliasing and blurring.
+pub fn rounded_rect_sdf(px: f64, py: f64, width: f64, height: f64, corners: Corners) -> f64 {
+    let radius = corners.radius.clamp(0.0, width.min(height).max(0.0) / 2.0);
+    let qx = (px - width / 2.0).abs() - (width / 2.0 - radius);
+    let qy = (py - height / 2.0).abs() - (height / 2.0 - radius);
+    let (ox, oy) = (qx.max(0.0), qy.max(0.0));
+    let outside = if corners.exponent == 2.0 {
+        (ox * ox + oy * oy).sqrt()
+    } else {
+        (ox.powf(corners.exponent) + oy.powf(corners.exponent)).powf(1.0 / corners.exponent)
+    };
+    outside + qx.max(qy).min(0.0) - radius
+}
+
+/// Fraction of the pixel at (x, y) covered by a rounded rectangle
+fn rounded_rect_coverage(x: u32, y: u32, width: u32, height: u32, corners: Corners) -> f64 {
+    let dist = rounded_rect_sdf(
+        x as f64 + 0.5,
+        y as f64 + 0.5,
+        width as f64,
+        height as f64,
+        corners,
+    );
+    (0.5 - dist).clamp(0.0, 1.0)
+}
+
+/// Draw a border ring just outside the frame, following its corners
+fn draw_border(canvas: &mut RgbaImage, rect: &FrameRect, corners: Corners, border: &Border) {
+    if border.width <= 0.0 {
+        return;
+    }
+    let color = parse_hex_color(&border.color);
+    let strength = border.opacity.clamp(0.0, 1.0) * color[3] as f64 / 255.0;
+    if strength <= 0.0 {
+        return;
+    }
 
-            let px = offset_x + x;
-            let py = offset_y + y;
+    let outer = corners.expanded(border.width);
+    let (fw, fh) = (rect.width as f64, rect.height as f64);
+    let reach = border.width.ceil() as i64 + 1;
+    let x0 = (rect.x as i64 - reach).max(0);
+    let y0 = (rect.y as i64 - reach).max(0);
+    let x1 = (rect.x as i64 + rect.width as i64 + reach).min(canvas.width() as i64);
+    let y1 = (rect.y as i64 + rect.height as i64 + reach).min(canvas.height() as i64);
 
-            if px < canvas.width() && py < canvas.height() {
-                let pixel = frame.get_pixel(x, y);
-                canvas.put_pixel(px, py, *pixel);
+    for py in y0..y1 {
+        for px in x0..x1 {
+            // Position relative to the frame's top-left corner
+            let fx = (px - rect.x as i64) as f64 + 0.5;
+            let fy = (py - rect.y as i64) as f64 + 0.5;
+            let inner_dist = rounded_rect_sdf(fx, fy, fw, fh, corners);
+            if inner_dist < -0.5 {
+                continue;
+            }
+            let outer_dist = rounded_rect_sdf(
+                fx + border.width,
+                fy + border.width,
+                fw + border.width * 2.0,
+                fh + border.width * 2.0,
+                outer,
+            );
+            let coverage = (0.5 - outer_dist).clamp(0.0, 1.0) - (0.5 - inner_dist).clamp(0.0, 1.0);
+            if coverage <= 0.0 {
+                continue;
             }
+
+            let alpha = (coverage * strength * 255.0).round() as u8;
+            let fg = Rgba([color[0], color[1], color[2], alpha]);
+            let blended = alpha_blend(canvas.get_pixel(px as u32, py as u32), &fg);
+            canvas.put_pixel(px as u32, py as u32, blended);
         }
     }
 }
 
-fn is_in_rounded_rect(x: u32, y: u32, width: u32, height: u32, radius: u32) -> bool {
-    if radius == 0 {
-        return true;
+/// Approximate a Gaussian blur with three successive box blurs per axis
+fn gaussian_blur(data: &mut [f32], width: usize, height: usize, sigma: f64) {
+    let mut scratch = vec![0f32; data.len()];
+    for radius in box_radii_for_gaussian(sigma) {
+        box_blur_horizontal(data, &mut scratch, width, height, radius);
+        box_blur_vertical(&scratch, data, width, height, radius);
     }
+}
 
-    let r = radius as f64;
+/// Box radii whose three-pass convolution approximates a Gaussian of `sigma`
+fn box_radii_for_gaussian(sigma: f64) -> [usize; 3] {
+    const PASSES: f64 = 3.0;
+    let ideal = (12.0 * sigma * sigma / PASSES + 1.0).sqrt();
+    let mut lower = ideal.floor() as i64;
+    if lower % 2 == 0 {
+        lower -= 1;
+    }
+    let lower = lower.max(1);
+    let upper = lower + 2;
+    let l = lower as f64;
+    let m_ideal = (12.0 * sigma * sigma - PASSES * l * l - 4.0 * PASSES * l - 3.0 * PASSES)
+        / (-4.0 * l - 4.0);
+    let m = m_ideal.round() as i64;
 
-    // Check each corner
-    let corners = [
-        (radius, radius),                                    // top-left
-        (width - radius, radius),                            // top-right
-        (radius, height - radius),                           // bottom-left
-        (width - radius, height - radius),                   // bottom-right
-    ];
+    let mut radii = [0usize; 3];
+    for (i, r) in radii.iter_mut().enumerate() {
+        let size = if (i as i64) < m { lower } else { upper };
+        *r = ((size - 1) / 2) as usize;
+    }
+    radii
+}
 
-    for &(cx, cy) in &corners {
-        let in_corner_region = (x < radius && y < radius && x <= cx && y <= cy)
-            || (x >= width - radius && y < radius && x >= cx && y <= cy)
-            || (x < radius && y >= height - radius && x <= cx && y >= cy)
-            || (x >= width - radius && y >= height - radius && x >= cx && y >= cy);
+fn box_blur_horizontal(src: &[f32], dst: &mut [f32], width: usize, height: usize, radius: usize) {
+    let norm = 1.0 / (radius * 2 + 1) as f32;
+    for y in 0..height {
+        let row = &src[y * width..(y + 1) * width];
+        let out = &mut dst[y * width..(y + 1) * width];
+        let mut sum: f32 = row.iter().take(radius + 1).sum();
+        for x in 0..width {
+            out[x] = sum * norm;
+            if x + radius + 1 < width {
+                sum += row[x + radius + 1];
+            }
+            if x >= radius {
+                sum -= row[x - radius];
+            }
+        }
+    }
+}
 
-        if in_corner_region {
-            let dx = x as f64 - cx as f64;
-            let dy = y as f64 - cy as f64;
-            if dx * dx + dy * dy > r * r {
-                return false;
+fn box_blur_vertical(src: &[f32], dst: &mut [f32], width: usize, height: usize, radius: usize) {
+    let norm = 1.0 / (radius * 2 + 1) as f32;
+    for x in 0..width {
+        let mut sum: f32 = (0..=radius.min(height - 1)).map(|y| src[y * width + x]).sum();
+        for y in 0..height {
+            dst[y * width + x] = sum * norm;
+            if y + radius + 1 < height {
+                sum += src[(y + radius + 1) * width + x];
+            }
+            if y >= radius {
+                sum -= src[(y - radius) * width + x];
             }
         }
     }
+}
+
+/// Split row `y` of a rounded rectangle into solid and anti-aliased pixels
+fn row_coverage(y: u32, width: u32, height: u32, corners: Corners) -> RowCoverage {
+    let radius = corners.radius.clamp(0.0, width.min(height) as f64 / 2.0);
+    let band = radius.ceil() as u32 + 1;
+
+    // Rows between the corners are fully covered
+    if y >= band && y + band < height {
+        return RowCoverage { solid: (0, width), edges: Vec::new() };
+    }
 
-    true
+    let mut row = RowCoverage::default();
+    let mut solid_start = None;
+    let mut solid_end = 0;
+    for x in 0..width {
+        // Only the corner bands can be partially covered
+        if x >= band && x + band < width {
+            if solid_start.is_none() {
+                solid_start = Some(x);
+            }
+            solid_end = x + 1;
+            continue;
+        }
+        let coverage = rounded_rect_coverage(x, y, width, height, corners);
+        if coverage >= 1.0 {
+            if solid_start.is_none() {
+                solid_start = Some(x);
+            }
+            solid_end = x + 1;
+        } else if coverage > 0.0 {
+            row.edges.push((x, (coverage * 255.0).round() as u8));
+        }
+    }
+    row.solid = (solid_start.unwrap_or(0), solid_end);
+    row
 }
 
-fn parse_hex_color(hex: &str) -> Rgba<u8> {
+pub fn parse_hex_color(hex: &str) -> Rgba<u8> {
     let hex = hex.trim_start_matches('#');
     let (r, g, b, a) = match hex.len() {
         6 => (
@@ -268,34 +614,10 @@ fn parse_hex_color(hex: &str) -> Rgba<u8> {
     Rgba([r, g, b, a])
 }
 
-fn lerp_u8(a: u8, b: u8, t: f64) -> u8 {
-    (a as f64 + (b as f64 - a as f64) * t).clamp(0.0, 255.0) as u8
-}
-
-fn alpha_blend(bg: &Rgba<u8>, fg: &Rgba<u8>) -> Rgba<u8> {
-    let fg_a = fg[3] as f64 / 255.0;
-    let bg_a = bg[3] as f64 / 255.0;
-    let out_a = fg_a + bg_a * (1.0 - fg_a);
-
-    if out_a < 0.001 {
-        return Rgba([0, 0, 0, 0]);
-    }
-
-    let r = (fg[0] as f64 * fg_a + bg[0] as f64 * bg_a * (1.0 - fg_a)) / out_a;
-    let g = (fg[1] as f64 * fg_a + bg[1] as f64 * bg_a * (1.0 - fg_a)) / out_a;
-    let b = (fg[2] as f64 * fg_a + bg[2] as f64 * bg_a * (1.0 - fg_a)) / out_a;
-
-    Rgba([
-        r.clamp(0.0, 255.0) as u8,
-        g.clamp(0.0, 255.0) as u8,
-        b.clamp(0.0, 255.0) as u8,
-        (out_a * 255.0).clamp(0.0, 255.0) as u8,
-    ])
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::effects::AspectRatio;
 
     #[test]
     fn test_parse_hex_color_6_digit() {
@@ -321,71 +643,318 @@ mod tests {
         assert_eq!(c, Rgba([255, 0, 0, 255]));
     }
 
+    fn circular(radius: u32) -> Corners {
+        Corners::new(radius, &CornerStyle::Circular)
+    }
+
     #[test]
-    fn test_lerp_u8_boundaries() {
-        assert_eq!(lerp_u8(0, 255, 0.0), 0);
-        assert_eq!(lerp_u8(0, 255, 1.0), 255);
+    fn test_rounded_rect_coverage_center() {
+        assert_eq!(rounded_rect_coverage(50, 50, 100, 100, circular(10)), 1.0);
     }
 
     #[test]
-    fn test_lerp_u8_midpoint() {
-        let mid = lerp_u8(0, 200, 0.5);
-        assert_eq!(mid, 100);
+    fn test_rounded_rect_coverage_corner_outside() {
+        // (0, 0) in a 100x100 rect with radius 20 — at the very corner
+        assert_eq!(rounded_rect_coverage(0, 0, 100, 100, circular(20)), 0.0);
     }
 
     #[test]
-    fn test_alpha_blend_opaque_fg() {
-        let bg = Rgba([100, 100, 100, 255]);
-        let fg = Rgba([200, 50, 50, 255]);
-        let result = alpha_blend(&bg, &fg);
-        assert_eq!(result, Rgba([200, 50, 50, 255]));
+    fn test_rounded_rect_coverage_radius_zero() {
+        // All pixels inside when radius is 0
+        assert_eq!(rounded_rect_coverage(0, 0, 100, 100, circular(0)), 1.0);
+        assert_eq!(rounded_rect_coverage(99, 99, 100, 100, circular(0)), 1.0);
     }
 
     #[test]
-    fn test_alpha_blend_transparent_fg() {
-        let bg = Rgba([100, 100, 100, 255]);
-        let fg = Rgba([200, 50, 50, 0]);
-        let result = alpha_blend(&bg, &fg);
-        assert_eq!(result[0], 100);
-        assert_eq!(result[1], 100);
-        assert_eq!(result[2], 100);
-        assert_eq!(result[3], 255);
+    fn test_rounded_rect_coverage_antialiased_arc() {
+        // Pixels straddling the arc are partially covered
+        let partial = (0..20)
+            .flat_map(|y| (0..20).map(move |x| (x, y)))
+            .map(|(x, y)| rounded_rect_coverage(x, y, 100, 100, circular(20)))
+            .filter(|c| *c > 0.0 && *c < 1.0)
+            .count();
+        assert!(partial > 10, "Expected anti-aliased edge pixels, got {partial}");
     }
 
     #[test]
-    fn test_alpha_blend_semi_transparent() {
-        let bg = Rgba([0, 0, 0, 255]);
-        let fg = Rgba([255, 255, 255, 128]);
-        let result = alpha_blend(&bg, &fg);
-        // fg alpha ~0.502, result should be roughly half white
-        assert!(result[0] > 100 && result[0] < 200);
-        assert_eq!(result[3], 255);
+    fn test_rounded_rect_coverage_oversized_radius_is_clamped() {
+        // Radius larger than half the frame must not underflow or panic
+        assert_eq!(rounded_rect_coverage(5, 5, 10, 10, circular(500)), 1.0);
+        assert_eq!(rounded_rect_coverage(0, 0, 10, 10, circular(500)), 0.0);
+        let row = row_coverage(0, 10, 10, circular(500));
+        assert!(row.solid.1 <= 10);
     }
 
     #[test]
-    fn test_alpha_blend_both_transparent() {
-        let bg = Rgba([100, 100, 100, 0]);
-        let fg = Rgba([200, 50, 50, 0]);
-        let result = alpha_blend(&bg, &fg);
-        assert_eq!(result[3], 0);
+    fn test_squircle_covers_more_of_the_corner() {
+        let squircle = Corners::new(40, &CornerStyle::Squircle);
+        let circle = circular(40);
+        // Along the diagonal the squircle extends further into the corner
+        assert_eq!(rounded_rect_coverage(9, 9, 200, 200, circle), 0.0);
+        assert_eq!(rounded_rect_coverage(9, 9, 200, 200, squircle), 1.0);
     }
 
     #[test]
-    fn test_is_in_rounded_rect_center() {
-        assert!(is_in_rounded_rect(50, 50, 100, 100, 10));
+    fn test_fit_image_cover_fills_and_crops() {
+        // 4x2 image: left half red, right half blue
+        let image = RgbaImage::from_fn(4, 2, |x, _| {
+            if x < 2 { Rgba([255, 0, 0, 255]) } else { Rgba([0, 0, 255, 255]) }
+        });
+        let fitted = fit_image(&image, 8, 8, &ImageFit::Cover);
+        assert_eq!(fitted.dimensions(), (8, 8));
+        // Scaled to 16x8 and center-cropped, so both halves remain visible
+        assert!(fitted.get_pixel(0, 4)[0] > 200);
+        assert!(fitted.get_pixel(7, 4)[2] > 200);
     }
 
     #[test]
-    fn test_is_in_rounded_rect_corner_outside() {
-        // (0, 0) in a 100x100 rect with radius 20 — at the very corner
-        assert!(!is_in_rounded_rect(0, 0, 100, 100, 20));
+    fn test_fit_image_contain_letterboxes() {
+        let image = RgbaImage::from_pixel(4, 2, Rgba([255, 255, 255, 255]));
+        let fitted = fit_image(&image, 8, 8, &ImageFit::Contain);
+        assert_eq!(fitted.dimensions(), (8, 8));
+        assert_eq!(*fitted.get_pixel(4, 0), Rgba([0, 0, 0, 255]));
+        assert!(fitted.get_pixel(4, 4)[0] > 200);
     }
 
     #[test]
-    fn test_is_in_rounded_rect_radius_zero() {
-        // All pixels inside when radius is 0
-        assert!(is_in_rounded_rect(0, 0, 100, 100, 0));
-        assert!(is_in_rounded_rect(99, 99, 100, 100, 0));
+    fn test_fit_image_tile_repeats() {
+        let image = RgbaImage::from_fn(2, 2, |x, y| Rgba([(x * 100) as u8, (y * 100) as u8, 0, 255]));
+        let fitted = fit_image(&image, 5, 5, &ImageFit::Tile);
+        assert_eq!(fitted.get_pixel(3, 2), image.get_pixel(1, 0));
+        assert_eq!(fitted.get_pixel(4, 4), image.get_pixel(0, 0));
+    }
+
+    #[test]
+    fn test_fit_image_stretch() {
+        let image = RgbaImage::from_pixel(3, 7, Rgba([10, 20, 30, 255]));
+        let fitted = fit_image(&image, 16, 9, &ImageFit::Stretch);
+        assert_eq!(fitted.dimensions(), (16, 9));
+    }
+
+    #[test]
+    fn test_dim_image() {
+        let mut image = RgbaImage::from_pixel(1, 1, Rgba([200, 100, 50, 255]));
+        dim_image(&mut image, 0.5);
+        assert_eq!(*image.get_pixel(0, 0), Rgba([100, 50, 25, 255]));
+    }
+
+    #[test]
+    fn test_render_background_missing_image_errors() {
+        let style = FrameStyle {
+            background: Background::Image {
+                path: "/nonexistent/wallpaper.png".to_string(),
+                fit: ImageFit::Cover,
+                blur: 0.0,
+                dim: 0.0,
+            },
+            ..FrameStyle::default()
+        };
+        let err = render_background(64, 64, &style).unwrap_err();
+        assert!(err.contains("/nonexistent/wallpaper.png"), "Unexpected error: {err}");
+    }
+
+    fn shadow_with(blur: f64, spread: f64) -> Shadow {
+        Shadow {
+            offset_x: 0.0,
+            offset_y: 0.0,
+            blur,
+            spread,
+            color: "#000000".to_string(),
+            opacity: 1.0,
+        }
+    }
+
+    fn mask_alpha_at(mask: &ShadowMask, x: i64, y: i64) -> u8 {
+        let (mx, my) = (x - mask.left, y - mask.top);
+        if mx < 0 || my < 0 || mx >= mask.width as i64 || my >= mask.height as i64 {
+            return 0;
+        }
+        mask.alpha[my as usize * mask.width + mx as usize]
+    }
+
+    #[test]
+    fn test_rounded_rect_sdf() {
+        assert!(rounded_rect_sdf(50.0, 50.0, 100.0, 100.0, circular(10)) < 0.0);
+        assert!((rounded_rect_sdf(100.0, 50.0, 100.0, 100.0, circular(10))).abs() < 1e-10);
+        // The corner point is outside the arc
+        assert!(rounded_rect_sdf(0.0, 0.0, 100.0, 100.0, circular(10)) > 0.0);
+    }
+
+    #[test]
+    fn test_box_radii_for_gaussian_grow_with_sigma() {
+        let small: usize = box_radii_for_gaussian(2.0).iter().sum();
+        let large: usize = box_radii_for_gaussian(16.0).iter().sum();
+        assert!(large > small);
+    }
+
+    #[test]
+    fn test_gaussian_blur_preserves_mass() {
+        let (w, h) = (64, 64);
+        let mut data = vec![0f32; w * h];
+        data[32 * w + 32] = 1.0;
+        gaussian_blur(&mut data, w, h, 4.0);
+        let total: f32 = data.iter().sum();
+        assert!((total - 1.0).abs() < 1e-3, "Blur should preserve mass, got {total}");
+        assert!(data[32 * w + 32] < 0.1);
+    }
+
+    #[test]
+    fn test_shadow_mask_hard_edge_without_blur() {
+        let rect = FrameRect { x: 20, y: 20, width: 60, height: 40 };
+        let mask = ShadowMask::new(&rect, circular(0), &shadow_with(0.0, 0.0));
+        assert_eq!(mask_alpha_at(&mask, 50, 40), 255);
+        assert_eq!(mask_alpha_at(&mask, 19, 40), 0);
+        assert_eq!(mask_alpha_at(&mask, 20, 40), 255);
+    }
+
+    #[test]
+    fn test_shadow_mask_follows_rounded_corners() {
+        let rect = FrameRect { x: 50, y: 50, width: 200, height: 200 };
+        let mask = ShadowMask::new(&rect, circular(40), &shadow_with(8.0, 0.0));
+        // Just outside the straight edge vs. the same distance outside the corner arc
+        let edge = mask_alpha_at(&mask, 46, 150);
+        let corner = mask_alpha_at(&mask, 50, 50);
+        assert!(edge > corner, "Corner should fall off faster: edge={edge} corner={corner}");
+    }
+
+    #[test]
+    fn test_shadow_mask_is_soft_and_symmetric() {
+        let rect = FrameRect { x: 100, y: 100, width: 100, height: 100 };
+        let mask = ShadowMask::new(&rect, circular(12), &shadow_with(32.0, 0.0));
+        let left = mask_alpha_at(&mask, 90, 150);
+        let right = mask_alpha_at(&mask, 209, 150);
+        assert!(left > 0 && left < 128);
+        assert!((left as i32 - right as i32).abs() <= 1);
+    }
+
+    #[test]
+    fn test_shadow_mask_spread_grows_shape() {
+        let rect = FrameRect { x: 50, y: 50, width: 100, height: 100 };
+        let mask = ShadowMask::new(&rect, circular(0), &shadow_with(0.0, 10.0));
+        assert_eq!(mask_alpha_at(&mask, 42, 100), 255);
+        assert_eq!(mask_alpha_at(&mask, 38, 100), 0);
+    }
+
+    #[test]
+    fn test_shadow_mask_draw_darkens_canvas() {
+        let rect = FrameRect { x: 10, y: 10, width: 20, height: 20 };
+        let mut shadow = shadow_with(0.0, 0.0);
+        shadow.opacity = 0.5;
+        let mask = ShadowMask::new(&rect, circular(0), &shadow);
+        let mut canvas = RgbaImage::from_pixel(40, 40, Rgba([200, 200, 200, 255]));
+        mask.draw(&mut canvas);
+        assert!(canvas.get_pixel(20, 20)[0] < 150);
+        assert_eq!(canvas.get_pixel(5, 5)[0], 200);
+    }
+
+    #[test]
+    fn test_row_coverage() {
+        let middle = row_coverage(50, 100, 100, circular(20));
+        assert_eq!(middle, RowCoverage { solid: (0, 100), edges: Vec::new() });
+
+        let top = row_coverage(0, 100, 100, circular(20));
+        assert!(top.solid.0 > 0 && top.solid.1 < 100);
+        assert!(!top.edges.is_empty());
+        assert!(top.edges.iter().all(|&(x, _)| x < top.solid.0 || x >= top.solid.1));
+
+        let square = row_coverage(0, 100, 100, circular(0));
+        assert_eq!(square, RowCoverage { solid: (0, 100), edges: Vec::new() });
+    }
+
+    fn solid_style(padding: u32, corner_radius: u32) -> FrameStyle {
+        FrameStyle {
+            background: Background::Solid { color: "#102030".to_string() },
+            padding,
+            corner_radius,
+            shadow: Shadow { opacity: 0.0, ..Shadow::default() },
+            ..FrameStyle::default()
+        }
+    }
+
+    #[test]
+    fn test_static_layer_compose_writes_frame_region() {
+        let style = solid_style(10, 4);
+        let layer = StaticLayer::new(60, 40, 40, 20, &style).unwrap();
+        assert_eq!(layer.rect, FrameRect { x: 10, y: 10, width: 40, height: 20 });
+
+        let frame = RgbaImage::from_pixel(40, 20, Rgba([255, 0, 0, 255]));
+        let out = layer.compose(&frame);
+        assert_eq!(*out.get_pixel(30, 20), Rgba([255, 0, 0, 255]));
+        // Padding and the clipped corner keep the background
+        assert_eq!(*out.get_pixel(5, 5), Rgba([0x10, 0x20, 0x30, 255]));
+        assert_eq!(*out.get_pixel(10, 10), Rgba([0x10, 0x20, 0x30, 255]));
+    }
+
+    #[test]
+    fn test_static_layer_is_reused_across_frames() {
+        let style = solid_style(8, 0);
+        let layer = StaticLayer::new(36, 36, 20, 20, &style).unwrap();
+
+        let red = layer.compose(&RgbaImage::from_pixel(20, 20, Rgba([255, 0, 0, 255])));
+        let green = layer.compose(&RgbaImage::from_pixel(20, 20, Rgba([0, 255, 0, 255])));
+        assert_eq!(*red.get_pixel(18, 18), Rgba([255, 0, 0, 255]));
+        assert_eq!(*green.get_pixel(18, 18), Rgba([0, 255, 0, 255]));
+        assert_eq!(red.get_pixel(2, 2), green.get_pixel(2, 2));
+    }
+
+    #[test]
+    fn test_static_layer_blends_corner_edges() {
+        let style = solid_style(10, 12);
+        let layer = StaticLayer::new(80, 80, 60, 60, &style).unwrap();
+        let out = layer.compose(&RgbaImage::from_pixel(60, 60, Rgba([255, 255, 255, 255])));
+
+        // Somewhere along the top-left arc there is a pixel between background and frame
+        let blended = (10..22)
+            .flat_map(|y| (10..22).map(move |x| (x, y)))
+            .map(|(x, y)| out.get_pixel(x, y)[0])
+            .any(|r| r > 0x10 && r < 255);
+        assert!(blended);
+    }
+
+    #[test]
+    fn test_static_layer_draws_border_outside_frame() {
+        let mut style = solid_style(20, 0);
+        style.border = Some(Border {
+            width: 4.0,
+            color: "#FF0000".to_string(),
+            opacity: 1.0,
+        });
+        let layer = StaticLayer::new(80, 80, 40, 40, &style).unwrap();
+        let out = layer.compose(&RgbaImage::from_pixel(40, 40, Rgba([0, 0, 255, 255])));
+
+        assert_eq!(*out.get_pixel(18, 40), Rgba([255, 0, 0, 255]));
+        assert_eq!(*out.get_pixel(20, 40), Rgba([0, 0, 255, 255]));
+        assert_eq!(*out.get_pixel(10, 40), Rgba([0x10, 0x20, 0x30, 255]));
+    }
+
+    #[test]
+    fn test_transparent_background_keeps_alpha() {
+        let style = FrameStyle {
+            background: Background::Transparent,
+            padding: 20,
+            corner_radius: 12,
+            shadow: Shadow { offset_x: 0.0, offset_y: 8.0, blur: 8.0, spread: 0.0, color: "#000000".to_string(), opacity: 0.5 },
+            ..FrameStyle::default()
+        };
+        let layer = StaticLayer::new(100, 100, 60, 60, &style).unwrap();
+        let out = layer.compose(&RgbaImage::from_pixel(60, 60, Rgba([255, 255, 255, 255])));
+
+        assert_eq!(out.get_pixel(2, 2)[3], 0);
+        assert_eq!(*out.get_pixel(50, 50), Rgba([255, 255, 255, 255]));
+        // The shadow below the frame is translucent black
+        let shadow = out.get_pixel(50, 84);
+        assert!(shadow[3] > 0 && shadow[3] < 255 && shadow[0] == 0, "got {:?}", shadow);
+        // Rounded corners fade out rather than showing a background color
+        assert_eq!(out.get_pixel(20, 20)[3], 0);
+    }
+
+    #[test]
+    fn test_static_layer_resizes_mismatched_frame() {
+        let style = solid_style(10, 0);
+        let layer = StaticLayer::new(60, 40, 40, 20, &style).unwrap();
+        let out = layer.compose(&RgbaImage::from_pixel(80, 40, Rgba([0, 0, 255, 255])));
+        assert_eq!(out.dimensions(), (60, 40));
+        assert_eq!(*out.get_pixel(30, 20), Rgba([0, 0, 255, 255]));
     }
 
     #[test]
@@ -398,4 +967,60 @@ mod tests {
         assert_eq!(w, 1920 + 128);
         assert_eq!(h, 1080 + 128);
     }
+
+    #[test]
+    fn test_calculate_canvas_size_vertical() {
+        let style = FrameStyle {
+            padding: 40,
+            aspect_ratio: AspectRatio::Ratio9x16,
+            ..FrameStyle::default()
+        };
+        let (w, h) = calculate_canvas_size(1920, 1080, &style);
+        assert_eq!(w, 2000);
+        assert_eq!(h, 3556);
+    }
+
+    #[test]
+    fn test_calculate_canvas_size_square() {
+        let style = FrameStyle {
+            padding: 0,
+            aspect_ratio: AspectRatio::Ratio1x1,
+            ..FrameStyle::default()
+        };
+        assert_eq!(calculate_canvas_size(1280, 720, &style), (1280, 1280));
+        assert_eq!(calculate_canvas_size(720, 1280, &style), (1280, 1280));
+    }
+
+    #[test]
+    fn test_calculate_canvas_size_widescreen_from_4x3() {
+        let style = FrameStyle {
+            padding: 0,
+            aspect_ratio: AspectRatio::Ratio16x9,
+            ..FrameStyle::default()
+        };
+        assert_eq!(calculate_canvas_size(1440, 1080, &style), (1920, 1080));
+    }
+
+    #[test]
+    fn test_calculate_frame_rect_centers_native_frame() {
+        let style = FrameStyle {
+            padding: 40,
+            aspect_ratio: AspectRatio::Ratio9x16,
+            ..FrameStyle::default()
+        };
+        let (cw, ch) = calculate_canvas_size(1920, 1080, &style);
+        let rect = calculate_frame_rect(cw, ch, 1920, 1080, &style);
+        assert_eq!(rect, FrameRect { x: 40, y: (3556 - 1080) / 2, width: 1920, height: 1080 });
+    }
+
+    #[test]
+    fn test_calculate_frame_rect_shrinks_to_fit_padding() {
+        let style = FrameStyle {
+            padding: 50,
+            ..FrameStyle::default()
+        };
+        let rect = calculate_frame_rect(1100, 1100, 2000, 1000, &style);
+        assert_eq!((rect.width, rect.height), (1000, 500));
+        assert_eq!((rect.x, rect.y), (50, 300));
+    }
 }
@@ -0,0 +1,164 @@
+use std::sync::OnceLock;
+
+use image::Rgba;
+
+/// Bits of precision in the linear to sRGB table index
+const LINEAR_INDEX_BITS: u32 = 12;
+
+/// Lookup tables between 8-bit sRGB and 16-bit linear light
+struct Tables {
+    to_linear: [u16; 256],
+    to_srgb: Vec<u8>,
+}
+
+fn tables() -> &'static Tables {
+    static TABLES: OnceLock<Tables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        let mut to_linear = [0u16; 256];
+        for (value, linear) in to_linear.iter_mut().enumerate() {
+            *linear = (srgb_to_linear(value as f64 / 255.0) * 65535.0).round() as u16;
+        }
+        // Sample each bucket at its center so truncating the index rounds
+        let shift = 16 - LINEAR_INDEX_BITS;
+        let to_srgb = (0..1u32 << LINEAR_INDEX_BITS)
+            .map(|index| {
+                let linear = ((index << shift) as f64 + (1u32 << shift) as f64 / 2.0) / 65535.0;
+                (linear_to_srgb(linear.min(1.0)) * 255.0).round() as u8
+            })
+            .collect();
+        Tables { to_linear, to_srgb }
+    })
+}
+
+fn srgb_to_linear(c: f64) -> f64 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f64) -> f64 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Composite `fg` over `bg` ("source over"), both straight-alpha sRGB.
+///
+/// Colors are premultiplied and mixed in linear light, so soft edges and
+/// shadows darken the way light does instead of going muddy. Uses only
+/// table lookups and integer math.
+pub fn alpha_blend(bg: &Rgba<u8>, fg: &Rgba<u8>) -> Rgba<u8> {
+    let fg_a = fg[3] as u32;
+    if fg_a == 255 {
+        return *fg;
+    }
+    let bg_a = bg[3] as u32;
+    if fg_a == 0 {
+        return *bg;
+    }
+
+    // Both weights are alpha scaled by 255, so they sum to at most 255 * 255
+    // and the sums below fit in a u32
+    let fg_weight = fg_a * 255;
+    let bg_weight = bg_a * (255 - fg_a);
+    let total = fg_weight + bg_weight;
+
+    let tables = tables();
+    let shift = 16 - LINEAR_INDEX_BITS;
+    let mut out = [0u8; 4];
+    for c in 0..3 {
+        let fg_linear = tables.to_linear[fg[c] as usize] as u32;
+        let bg_linear = tables.to_linear[bg[c] as usize] as u32;
+        let linear = (fg_linear * fg_weight + bg_linear * bg_weight + total / 2) / total;
+        out[c] = tables.to_srgb[(linear >> shift) as usize];
+    }
+    out[3] = ((total + 127) / 255) as u8;
+    Rgba(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(actual: Rgba<u8>, expected: Rgba<u8>) {
+        let close = (0..4).all(|c| (actual[c] as i32 - expected[c] as i32).abs() <= 1);
+        assert!(close, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn test_alpha_blend_opaque_fg() {
+        let bg = Rgba([100, 100, 100, 255]);
+        let fg = Rgba([200, 50, 50, 255]);
+        let result = alpha_blend(&bg, &fg);
+        assert_eq!(result, Rgba([200, 50, 50, 255]));
+    }
+
+    #[test]
+    fn test_alpha_blend_transparent_fg() {
+        let bg = Rgba([100, 100, 100, 255]);
+        let fg = Rgba([200, 50, 50, 0]);
+        let result = alpha_blend(&bg, &fg);
+        assert_eq!(result[0], 100);
+        assert_eq!(result[1], 100);
+        assert_eq!(result[2], 100);
+        assert_eq!(result[3], 255);
+    }
+
+    #[test]
+    fn test_alpha_blend_semi_transparent() {
+        let bg = Rgba([0, 0, 0, 255]);
+        let fg = Rgba([255, 255, 255, 128]);
+        let result = alpha_blend(&bg, &fg);
+        // fg alpha ~0.502, result should be roughly half white
+        assert!(result[0] > 100 && result[0] < 200);
+        assert_eq!(result[3], 255);
+    }
+
+    #[test]
+    fn test_alpha_blend_both_transparent() {
+        let bg = Rgba([100, 100, 100, 0]);
+        let fg = Rgba([200, 50, 50, 0]);
+        let result = alpha_blend(&bg, &fg);
+        assert_eq!(result[3], 0);
+    }
+
+    #[test]
+    fn test_srgb_round_trip() {
+        let tables = tables();
+        let shift = 16 - LINEAR_INDEX_BITS;
+        for value in 0..=255u8 {
+            let linear = tables.to_linear[value as usize];
+            assert_eq!(tables.to_srgb[(linear >> shift) as usize], value);
+        }
+    }
+
+    // Reference values from the floating point sRGB formulas
+
+    #[test]
+    fn test_shadow_over_light_background() {
+        let white = Rgba([255, 255, 255, 255]);
+        assert_near(alpha_blend(&white, &Rgba([0, 0, 0, 128])), Rgba([187, 187, 187, 255]));
+        assert_near(alpha_blend(&white, &Rgba([0, 0, 0, 64])), Rgba([224, 224, 224, 255]));
+        let tinted = Rgba([30, 60, 120, 100]);
+        assert_near(alpha_blend(&Rgba([240, 240, 240, 255]), &tinted), Rgba([193, 195, 204, 255]));
+    }
+
+    #[test]
+    fn test_shadow_over_dark_background() {
+        assert_near(alpha_blend(&Rgba([32, 32, 32, 255]), &Rgba([0, 0, 0, 128])), Rgba([20, 20, 20, 255]));
+        let glow = Rgba([255, 255, 255, 64]);
+        assert_near(alpha_blend(&Rgba([24, 24, 27, 255]), &glow), Rgba([139, 139, 139, 255]));
+    }
+
+    #[test]
+    fn test_blend_over_translucent_background() {
+        let red = Rgba([255, 0, 0, 128]);
+        assert_eq!(alpha_blend(&Rgba([0, 0, 0, 0]), &red), red);
+        let blue = Rgba([0, 0, 255, 128]);
+        assert_near(alpha_blend(&red, &blue), Rgba([156, 0, 213, 192]));
+    }
+}
@@ -0,0 +1,357 @@
+use image::RgbaImage;
+
+use crate::models::effects::{FrameChrome, Shadow};
+use crate::processing::background::{parse_hex_color, Corners, FrameRect, ShadowMask};
+use crate::processing::shape::Shape;
+use crate::processing::text::Font;
+
+/// Chrome sizes below are in points for a frame this wide and scale with
+/// the recording, so a Retina capture gets proportionally larger chrome
+const REFERENCE_WIDTH: f64 = 1440.0;
+
+const TITLE_BAR_HEIGHT: f64 = 28.0;
+const BROWSER_BAR_HEIGHT: f64 = 44.0;
+const TRAFFIC_LIGHT_DIAMETER: f64 = 12.0;
+const TRAFFIC_LIGHT_SPACING: f64 = 20.0;
+/// Center of the first traffic light from the left edge
+const TRAFFIC_LIGHT_INSET: f64 = 20.0;
+const TRAFFIC_LIGHT_COLORS: [&str; 3] = ["#FF5F57", "#FEBC2E", "#28C840"];
+const LABEL_TEXT_SIZE: f64 = 13.0;
+const URL_FIELD_HEIGHT: f64 = 28.0;
+const URL_FIELD_PADDING: f64 = 12.0;
+const BEZEL_WIDTH: f64 = 24.0;
+const CAMERA_DIAMETER: f64 = 6.0;
+const BASE_HEIGHT: f64 = 20.0;
+const BASE_OVERHANG: f64 = 80.0;
+
+/// Light and dark variants of the toolbar colors
+struct Palette {
+    bar: &'static str,
+    divider: &'static str,
+    label: &'static str,
+    field: &'static str,
+}
+
+const LIGHT: Palette = Palette {
+    bar: "#E8E8EA",
+    divider: "#D1D1D6",
+    label: "#4D4D4D",
+    field: "#FFFFFF",
+};
+
+const DARK: Palette = Palette {
+    bar: "#2C2C2E",
+    divider: "#1C1C1E",
+    label: "#C7C7CC",
+    field: "#1C1C1E",
+};
+
+/// Extra space the chrome needs around the video on each side
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChromeInsets {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+impl ChromeInsets {
+    pub fn new(chrome: &FrameChrome, frame_width: u32) -> Self {
+        let scale = Scale::new(frame_width);
+        match chrome {
+            FrameChrome::None => Self::default(),
+            FrameChrome::Window { .. } => Self { top: scale.px(TITLE_BAR_HEIGHT), ..Self::default() },
+            FrameChrome::Browser { .. } => Self { top: scale.px(BROWSER_BAR_HEIGHT), ..Self::default() },
+            FrameChrome::Laptop => {
+                let bezel = scale.px(BEZEL_WIDTH);
+                let side = bezel + scale.px(BASE_OVERHANG);
+                Self {
+                    top: bezel,
+                    right: side,
+                    bottom: bezel + scale.px(BASE_HEIGHT),
+                    left: side,
+                }
+            }
+        }
+    }
+
+    pub fn horizontal(&self) -> u32 {
+        self.left + self.right
+    }
+
+    pub fn vertical(&self) -> u32 {
+        self.top + self.bottom
+    }
+}
+
+/// Converts reference points into pixels for a given frame width
+#[derive(Clone, Copy)]
+struct Scale(f64);
+
+impl Scale {
+    fn new(frame_width: u32) -> Self {
+        Self(frame_width as f64 / REFERENCE_WIDTH)
+    }
+
+    fn f(self, points: f64) -> f64 {
+        points * self.0
+    }
+
+    /// Whole pixels, never less than one
+    fn px(self, points: f64) -> u32 {
+        (self.f(points).round() as u32).max(1)
+    }
+}
+
+/// The rectangle around the video that gets the frame's rounded corners,
+/// shadow and border: the video plus its title bar, or the laptop lid
+pub fn body_rect(chrome: &FrameChrome, video: &FrameRect) -> FrameRect {
+    let insets = ChromeInsets::new(chrome, video.width);
+    // The laptop base hangs below and beside the lid; the bezel is even
+    let (side, bottom) = match chrome {
+        FrameChrome::Laptop => (insets.top, insets.top),
+        _ => (0, 0),
+    };
+    FrameRect {
+        x: video.x.saturating_sub(side),
+        y: video.y.saturating_sub(insets.top),
+        width: video.width + side * 2,
+        height: video.height + insets.top + bottom,
+    }
+}
+
+/// Draw the chrome around `video`. Runs after the body's shadow and border
+/// and before the video itself, which covers the rest of the body.
+pub fn draw_chrome(
+    canvas: &mut RgbaImage,
+    chrome: &FrameChrome,
+    video: &FrameRect,
+    corners: Corners,
+    shadow: &Shadow,
+) {
+    let body = body_rect(chrome, video);
+    let scale = Scale::new(video.width);
+    match chrome {
+        FrameChrome::None => {}
+        FrameChrome::Window { dark, .. } | FrameChrome::Browser { dark, .. } => {
+            let palette = if *dark { &DARK } else { &LIGHT };
+            draw_toolbar(canvas, &body, video, corners, palette, scale);
+            let font = Font::system();
+            match chrome {
+                FrameChrome::Window { title, .. } if !title.is_empty() => {
+                    draw_title(canvas, &font, title, &body, video, palette, scale);
+                }
+                FrameChrome::Browser { url, .. } => {
+                    draw_url_field(canvas, &font, url, &body, video, palette, scale);
+                }
+                _ => {}
+            }
+        }
+        FrameChrome::Laptop => draw_laptop(canvas, &body, corners, shadow, scale),
+    }
+}
+
+/// Title bar background, divider and traffic lights
+fn draw_toolbar(
+    canvas: &mut RgbaImage,
+    body: &FrameRect,
+    video: &FrameRect,
+    corners: Corners,
+    palette: &Palette,
+    scale: Scale,
+) {
+    let bar_bottom = video.y as f64;
+    // Stop at the video so its rounded bottom corners blend onto the background
+    Shape::from_rect(body, corners).fill_above(canvas, parse_hex_color(palette.bar), bar_bottom);
+
+    let divider = scale.f(1.0).max(1.0);
+    Shape::rect(body.x as f64, bar_bottom - divider, body.width as f64, divider)
+        .fill(canvas, parse_hex_color(palette.divider));
+
+    let center_y = (body.y as f64 + bar_bottom) / 2.0;
+    let diameter = scale.f(TRAFFIC_LIGHT_DIAMETER);
+    for (i, color) in TRAFFIC_LIGHT_COLORS.iter().enumerate() {
+        let center_x = body.x as f64 + scale.f(TRAFFIC_LIGHT_INSET + TRAFFIC_LIGHT_SPACING * i as f64);
+        Shape::circle(center_x, center_y, diameter / 2.0).fill(canvas, parse_hex_color(color));
+    }
+}
+
+/// Left edge of the toolbar area that is free of traffic lights
+fn toolbar_content_left(body: &FrameRect, scale: Scale) -> f64 {
+    let lights_width = TRAFFIC_LIGHT_INSET + TRAFFIC_LIGHT_SPACING * 2.0 + TRAFFIC_LIGHT_DIAMETER;
+    body.x as f64 + scale.f(lights_width + URL_FIELD_PADDING)
+}
+
+fn draw_title(
+    canvas: &mut RgbaImage,
+    font: &Font,
+    title: &str,
+    body: &FrameRect,
+    video: &FrameRect,
+    palette: &Palette,
+    scale: Scale,
+) {
+    let size = scale.f(LABEL_TEXT_SIZE);
+
+    // Centered on the window, keeping clear of the traffic lights on both sides
+    let inset = toolbar_content_left(body, scale) - body.x as f64;
+    let title = font.truncate(title, size, body.width as f64 - inset * 2.0);
+    let width = font.text_width(&title, size);
+    let x = body.x as f64 + (body.width as f64 - width) / 2.0;
+    let y = (body.y as f64 + video.y as f64 - font.line_height(size)) / 2.0;
+    font.draw_text(canvas, &title, x, y, size, parse_hex_color(palette.label));
+}
+
+fn draw_url_field(
+    canvas: &mut RgbaImage,
+    font: &Font,
+    url: &str,
+    body: &FrameRect,
+    video: &FrameRect,
+    palette: &Palette,
+    scale: Scale,
+) {
+    let height = scale.f(URL_FIELD_HEIGHT);
+    let left = toolbar_content_left(body, scale);
+    let right = body.x as f64 + body.width as f64 - scale.f(URL_FIELD_PADDING);
+    // Half the window wide and centered, unless the traffic lights are in the way
+    let width = (body.width as f64 / 2.0).min(right - left);
+    if width <= 0.0 {
+        return;
+    }
+    let x = (body.x as f64 + (body.width as f64 - width) / 2.0).max(left);
+    let y = (body.y as f64 + video.y as f64 - height) / 2.0;
+
+    let field = Shape {
+        x,
+        y,
+        width,
+        height,
+        corners: Corners { radius: height / 4.0, exponent: 2.0 },
+    };
+    field.fill(canvas, parse_hex_color(palette.field));
+
+    if url.is_empty() {
+        return;
+    }
+    let size = scale.f(LABEL_TEXT_SIZE);
+    let padding = scale.f(URL_FIELD_PADDING);
+    let text = font.truncate(url, size, width - padding * 2.0);
+    let text_y = y + (height - font.line_height(size)) / 2.0;
+    font.draw_text(canvas, &text, x + padding, text_y, size, parse_hex_color(palette.label));
+}
+
+fn draw_laptop(canvas: &mut RgbaImage, lid: &FrameRect, corners: Corners, shadow: &Shadow, scale: Scale) {
+    let overhang = scale.px(BASE_OVERHANG);
+    let base = FrameRect {
+        x: lid.x.saturating_sub(overhang),
+        y: lid.y + lid.height,
+        width: lid.width + overhang * 2,
+        height: scale.px(BASE_HEIGHT),
+    };
+    let base_corners = Corners { radius: base.height as f64 / 2.0, exponent: 4.0 };
+
+    ShadowMask::new(&base, base_corners, shadow).draw(canvas);
+    Shape::from_rect(lid, corners).fill(canvas, parse_hex_color("#111113"));
+    Shape::from_rect(&base, base_corners).fill(canvas, parse_hex_color("#D4D5D9"));
+
+    // Thumb notch along the front edge of the base
+    let notch_width = lid.width as f64 * 0.12;
+    let notch_height = base.height as f64 * 0.35;
+    Shape {
+        x: lid.x as f64 + (lid.width as f64 - notch_width) / 2.0,
+        y: base.y as f64,
+        width: notch_width,
+        height: notch_height,
+        corners: Corners { radius: notch_height / 2.0, exponent: 2.0 },
+    }
+    .fill(canvas, parse_hex_color("#A9AAAE"));
+
+    // Camera in the middle of the top bezel
+    let bezel = scale.px(BEZEL_WIDTH) as f64;
+    Shape::circle(
+        lid.x as f64 + lid.width as f64 / 2.0,
+        lid.y as f64 + bezel / 2.0,
+        scale.f(CAMERA_DIAMETER) / 2.0,
+    )
+    .fill(canvas, parse_hex_color("#2A2A2E"));
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    fn window() -> FrameChrome {
+        FrameChrome::Window { title: String::new(), dark: false }
+    }
+
+    fn no_shadow() -> Shadow {
+        Shadow { opacity: 0.0, ..Shadow::default() }
+    }
+
+    #[test]
+    fn test_insets_scale_with_frame_width() {
+        assert_eq!(ChromeInsets::new(&FrameChrome::None, 1440), ChromeInsets::default());
+        assert_eq!(ChromeInsets::new(&window(), 1440).top, 28);
+        assert_eq!(ChromeInsets::new(&window(), 2880).top, 56);
+
+        let laptop = ChromeInsets::new(&FrameChrome::Laptop, 1440);
+        assert_eq!(laptop, ChromeInsets { top: 24, right: 104, bottom: 44, left: 104 });
+        assert_eq!((laptop.horizontal(), laptop.vertical()), (208, 68));
+    }
+
+    #[test]
+    fn test_body_rect() {
+        let video = FrameRect { x: 200, y: 100, width: 1440, height: 900 };
+        assert_eq!(body_rect(&FrameChrome::None, &video), video);
+        assert_eq!(
+            body_rect(&window(), &video),
+            FrameRect { x: 200, y: 72, width: 1440, height: 928 }
+        );
+        assert_eq!(
+            body_rect(&FrameChrome::Laptop, &video),
+            FrameRect { x: 176, y: 76, width: 1488, height: 948 }
+        );
+    }
+
+    #[test]
+    fn test_window_draws_bar_and_traffic_lights() {
+        let video = FrameRect { x: 20, y: 48, width: 1440, height: 100 };
+        let mut canvas = RgbaImage::from_pixel(1480, 170, Rgba([0, 0, 255, 255]));
+        draw_chrome(&mut canvas, &window(), &video, Corners { radius: 0.0, exponent: 2.0 }, &no_shadow());
+
+        // Bar color above the video, untouched background around it
+        assert_eq!(*canvas.get_pixel(700, 25), parse_hex_color(LIGHT.bar));
+        assert_eq!(*canvas.get_pixel(700, 10), Rgba([0, 0, 255, 255]));
+        assert_eq!(*canvas.get_pixel(700, 60), Rgba([0, 0, 255, 255]));
+        // Red traffic light center
+        assert_eq!(*canvas.get_pixel(40, 34), parse_hex_color("#FF5F57"));
+        assert_eq!(*canvas.get_pixel(80, 34), parse_hex_color("#28C840"));
+    }
+
+    #[test]
+    fn test_browser_draws_url_field() {
+        let chrome = FrameChrome::Browser { url: "example.com".to_string(), dark: true };
+        let video = FrameRect { x: 0, y: 44, width: 1440, height: 50 };
+        let mut canvas = RgbaImage::new(1440, 94);
+        draw_chrome(&mut canvas, &chrome, &video, Corners { radius: 0.0, exponent: 2.0 }, &no_shadow());
+
+        assert_eq!(*canvas.get_pixel(1400, 22), parse_hex_color(DARK.bar));
+        // The field sits in the middle of the bar; its far end has no text
+        assert_eq!(*canvas.get_pixel(1070, 22), parse_hex_color(DARK.field));
+    }
+
+    #[test]
+    fn test_laptop_draws_lid_and_base() {
+        let video = FrameRect { x: 104, y: 24, width: 1440, height: 100 };
+        let mut canvas = RgbaImage::from_pixel(1648, 200, Rgba([255, 255, 255, 255]));
+        draw_chrome(&mut canvas, &FrameChrome::Laptop, &video, Corners { radius: 0.0, exponent: 2.0 }, &no_shadow());
+
+        // Bezel left of the video, base below the lid and wider than it
+        assert_eq!(*canvas.get_pixel(90, 70), parse_hex_color("#111113"));
+        assert_eq!(*canvas.get_pixel(40, 160), parse_hex_color("#D4D5D9"));
+        assert_eq!(*canvas.get_pixel(40, 70), Rgba([255, 255, 255, 255]));
+    }
+}
@@ -1,14 +1,32 @@
 use image::RgbaImage;
 
-use crate::models::effects::{FrameStyle, ZoomConfig};
+use crate::models::annotations::AnnotationSpace;
 use crate::models::events::RecordedEvents;
-use crate::processing::background;
-use crate::processing::zoom::{self, FrameViewport, ZoomKeyframe};
+use crate::models::project::Project;
+use crate::processing::annotations::AnnotationLayer;
+use crate::processing::background::{self, StaticLayer};
+use crate::processing::cursor::CursorLayer;
+use crate::processing::grading::ColorGrader;
+use crate::processing::keystrokes::KeystrokeLayer;
+use crate::processing::layer::{ExportGeometry, FrameContext, Layer, Surfaces};
+use crate::processing::overlay::OverlayLayer;
+use crate::processing::redactions::RedactionLayer;
+use crate::processing::watermark::WatermarkLayer;
+use crate::processing::zoom::{self, FrameViewport, ZoomKeyframe, ZoomLayer};
 
-/// The compositor combines all layers for a single frame:
-/// Background -> Shadow -> Rounded Frame -> Video Content (with zoom) -> Cursor
+/// The compositor renders each frame through an ordered stack of layers.
+/// For a project it is:
+/// Redactions -> Zoom -> Color Grade -> Source Annotations -> Spotlight and Cursor ->
+/// Overlay Video -> Background, Shadow and Rounded Frame -> Canvas Annotations ->
+/// Keystrokes -> Watermark
+///
+/// The cursor sprite is left out when the recording already shows the
+/// system cursor; the spotlight and click highlights still draw.
+///
+/// Layers before the frame draw onto the video at source size, so the
+/// rounded corners clip them; layers after it draw onto the output canvas.
 pub struct Compositor {
-    pub frame_style: FrameStyle,
+    pub layers: Vec<Box<dyn Layer>>,
     pub zoom_keyframes: Vec<ZoomKeyframe>,
     pub source_width: u32,
     pub source_height: u32,
@@ -17,30 +35,84 @@ pub struct Compositor {
 }
 
 impl Compositor {
+    /// Build and prepare the layers for `project`. `overlay_size` is the
+    /// size of the opened overlay video, if the project has one.
     pub fn new(
-        frame_style: FrameStyle,
-        zoom_config: &ZoomConfig,
+        project: &Project,
         events: &RecordedEvents,
         source_width: u32,
         source_height: u32,
-    ) -> Self {
+        fps: f64,
+        overlay_size: Option<(u32, u32)>,
+    ) -> Result<Self, String> {
+        let pixel_scale = if events.display_width > 0.0 {
+            source_width as f64 / events.display_width
+        } else {
+            1.0
+        };
+        let frame_style = &project.frame_style;
+        let events = events.scaled_to(source_width as f64, source_height as f64);
         let click_events = events.click_events();
-        let zoom_keyframes = zoom::generate_zoom_keyframes(&click_events, zoom_config);
+        let zoom_keyframes =
+            zoom::generate_zoom_keyframes(&click_events, &project.zoom_config, source_width as f64);
 
         let (output_width, output_height) = background::calculate_canvas_size(
             source_width,
             source_height,
-            &frame_style,
+            frame_style,
         );
 
-        Self {
+        let mut layers: Vec<Box<dyn Layer>> = vec![
+            Box::new(RedactionLayer::new(&project.redactions, source_width, source_height)?),
+            Box::new(ZoomLayer::new(zoom_keyframes.clone(), &project.zoom_config)),
+        ];
+        if let Some(grader) = ColorGrader::new(&project.color_grade)? {
+            layers.push(Box::new(grader));
+        }
+        layers.push(Box::new(AnnotationLayer::new(&project.annotations, AnnotationSpace::Source)?));
+        layers.push(Box::new(CursorLayer::new(
+            &project.cursor_config,
+            events.mouse_events.clone(),
+            pixel_scale,
+            !project.cursor_in_video,
+        )?));
+        if let (Some(track), Some((width, height))) = (&project.overlay, overlay_size) {
+            layers.push(Box::new(OverlayLayer::new(track, width, height, source_width, source_height)));
+        }
+        layers.push(Box::new(StaticLayer::new(
+            output_width,
+            output_height,
+            source_width,
+            source_height,
             frame_style,
+        )?));
+        layers.push(Box::new(AnnotationLayer::new(&project.annotations, AnnotationSpace::Canvas)?));
+        if let Some(keystrokes) = KeystrokeLayer::new(&events.key_events, &project.keystroke_config)? {
+            layers.push(Box::new(keystrokes));
+        }
+        if let Some(watermark) = &frame_style.watermark {
+            layers.push(Box::new(WatermarkLayer::new(watermark, output_width, output_height)?));
+        }
+
+        let geometry = ExportGeometry {
+            source_width,
+            source_height,
+            canvas_width: output_width,
+            canvas_height: output_height,
+            frame_interval_ms: 1000.0 / fps.max(1.0),
+        };
+        for layer in &mut layers {
+            layer.prepare(&geometry)?;
+        }
+
+        Ok(Self {
+            layers,
             zoom_keyframes,
             source_width,
             source_height,
             output_width,
             output_height,
-        }
+        })
     }
 
     /// Get the viewport (crop region) for a frame at the given timestamp
@@ -53,61 +125,142 @@ impl Compositor {
         )
     }
 
-    /// Compose a single frame with all effects applied
+    /// Compose a single frame by rendering every layer in order
     pub fn compose_frame(
         &self,
         source_frame: &RgbaImage,
+        overlay_frame: Option<&RgbaImage>,
         time_ms: u64,
     ) -> RgbaImage {
-        // 1. Apply zoom (crop and scale the source frame)
-        let viewport = self.get_viewport(time_ms);
-        let zoomed_frame = apply_zoom_crop(source_frame, &viewport, self.source_width, self.source_height);
-
-        // 2. Render background
-        let bg = background::render_background(
-            self.output_width,
-            self.output_height,
-            &self.frame_style,
-        );
-
-        // 3. Composite frame onto background
-        background::composite_frame(&bg, &zoomed_frame, &self.frame_style)
+        let ctx = FrameContext {
+            time_ms,
+            viewport: self.get_viewport(time_ms),
+            overlay_frame,
+        };
+        let mut surfaces = Surfaces::new(source_frame);
+        for layer in &self.layers {
+            layer.render(&mut surfaces, &ctx);
+        }
+        surfaces.canvas
     }
 }
 
-/// Crop and scale a source frame according to the zoom viewport
-fn apply_zoom_crop(
-    source: &RgbaImage,
-    viewport: &FrameViewport,
-    target_width: u32,
-    target_height: u32,
-) -> RgbaImage {
-    if (viewport.zoom - 1.0).abs() < 0.01 {
-        // No zoom: return source as-is (or resized to target)
-        if source.width() == target_width && source.height() == target_height {
-            return source.clone();
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+    use crate::models::effects::{SpotlightMode, SpotlightRange};
+    use crate::models::events::{EventType, MouseButton, MouseEvent};
+
+    const GRAY: Rgba<u8> = Rgba([128, 128, 128, 255]);
+
+    fn project() -> Project {
+        Project::new(
+            "test".to_string(),
+            "Test".to_string(),
+            "video.mp4".to_string(),
+            "events.json".to_string(),
+            64,
+            36,
+            30.0,
+        )
+    }
+
+    fn compositor_for(project: &Project) -> Compositor {
+        compositor_with_events(project, &RecordedEvents::new(64.0, 36.0))
+    }
+
+    fn compositor_with_events(project: &Project, events: &RecordedEvents) -> Compositor {
+        Compositor::new(project, events, 64, 36, 30.0, None).unwrap()
+    }
+
+    fn event_at_center(event_type: EventType) -> RecordedEvents {
+        let mut events = RecordedEvents::new(64.0, 36.0);
+        events.mouse_events.push(MouseEvent {
+            timestamp_ms: 0,
+            x: 32.0,
+            y: 18.0,
+            event_type,
+            button: MouseButton::Left,
+        });
+        events
+    }
+
+    fn click_at_center() -> RecordedEvents {
+        event_at_center(EventType::Click)
+    }
+
+    fn compositor() -> Compositor {
+        compositor_for(&project())
+    }
+
+    /// Paints the whole canvas, to show where in the stack it runs
+    struct Paint(Rgba<u8>);
+
+    impl Layer for Paint {
+        fn render(&self, surfaces: &mut Surfaces, _ctx: &FrameContext) {
+            for pixel in surfaces.canvas.pixels_mut() {
+                *pixel = self.0;
+            }
         }
-        return image::imageops::resize(
-            source,
-            target_width,
-            target_height,
-            image::imageops::FilterType::Lanczos3,
-        );
     }
 
-    // Crop the viewport region from the source
-    let crop_x = viewport.x.max(0.0) as u32;
-    let crop_y = viewport.y.max(0.0) as u32;
-    let crop_w = (viewport.width as u32).min(source.width() - crop_x);
-    let crop_h = (viewport.height as u32).min(source.height() - crop_y);
-
-    let cropped = image::imageops::crop_imm(source, crop_x, crop_y, crop_w, crop_h).to_image();
-
-    // Scale back up to the original frame size
-    image::imageops::resize(
-        &cropped,
-        target_width,
-        target_height,
-        image::imageops::FilterType::Lanczos3,
-    )
+    #[test]
+    fn test_default_project_frames_the_video() {
+        let compositor = compositor();
+        let source = RgbaImage::from_pixel(64, 36, GRAY);
+        let frame = compositor.compose_frame(&source, None, 0);
+
+        assert_eq!(frame.dimensions(), (compositor.output_width, compositor.output_height));
+        assert_eq!(*frame.get_pixel(frame.width() / 2, frame.height() / 2), GRAY);
+    }
+
+    #[test]
+    fn test_layers_render_in_order() {
+        let mut compositor = compositor();
+        let source = RgbaImage::from_pixel(64, 36, GRAY);
+        let red = Rgba([255, 0, 0, 255]);
+        let blue = Rgba([0, 0, 255, 255]);
+
+        compositor.layers.push(Box::new(Paint(red)));
+        compositor.layers.push(Box::new(Paint(blue)));
+        assert_eq!(*compositor.compose_frame(&source, None, 0).get_pixel(0, 0), blue);
+
+        let last = compositor.layers.len() - 1;
+        compositor.layers.swap(last - 1, last);
+        assert_eq!(*compositor.compose_frame(&source, None, 0).get_pixel(0, 0), red);
+    }
+
+    #[test]
+    fn test_cursor_sprite_drawn_only_when_not_captured() {
+        let source = RgbaImage::from_pixel(64, 36, GRAY);
+        let mut project = project();
+        let plain = compositor_for(&project).compose_frame(&source, None, 100);
+        let drawn = compositor_with_events(&project, &click_at_center()).compose_frame(&source, None, 100);
+
+        // A captured cursor still gets click highlights, just no sprite
+        project.cursor_in_video = true;
+        let captured = compositor_with_events(&project, &click_at_center()).compose_frame(&source, None, 100);
+        assert_ne!(captured, plain);
+        assert_ne!(drawn, captured);
+    }
+
+    #[test]
+    fn test_spotlight_dims_new_and_legacy_projects() {
+        let source = RgbaImage::from_pixel(64, 36, GRAY);
+        let events = event_at_center(EventType::Move);
+        let mut project = project();
+        let plain = compositor_with_events(&project, &events).compose_frame(&source, None, 1000);
+
+        project.cursor_config.spotlight = SpotlightMode::Ranges { ranges: vec![SpotlightRange { start_ms: 0, end_ms: 2000 }] };
+        project.cursor_config.spotlight_radius = 4;
+        project.cursor_config.spotlight_feather = 4;
+        for cursor_in_video in [false, true] {
+            project.cursor_in_video = cursor_in_video;
+            let lit = compositor_with_events(&project, &events).compose_frame(&source, None, 1000);
+            let dimmed = lit.pixels().zip(plain.pixels()).filter(|(l, p)| l[0] < p[0]).count();
+            assert!(dimmed > 100, "Spotlight should dim the video with cursor_in_video={}", cursor_in_video);
+        }
+    }
 }
@@ -1,5 +1,35 @@
-use crate::models::effects::CursorConfig;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use image::imageops::{self, FilterType};
+use image::{Rgba, RgbaImage};
+
+use crate::models::effects::{CursorConfig, CursorImage, SpotlightMode};
 use crate::models::events::{EventType, MouseEvent};
+use crate::processing::background::parse_hex_color;
+use crate::processing::blend::alpha_blend;
+use crate::processing::layer::{FrameContext, Layer, Surfaces};
+use crate::processing::shape::{distance_to_segment, point_in_polygon};
+use crate::processing::zoom::FrameViewport;
+
+/// Height of the cursor in display points at scale 1.0
+const CURSOR_BASE_SIZE: f64 = 24.0;
+
+/// How long a `WhileMoving` spotlight lingers after the cursor stops
+const SPOTLIGHT_HOLD_MS: u64 = 500;
+/// Time the spotlight takes to fade in or out
+const SPOTLIGHT_FADE_MS: u64 = 250;
+
+/// Classic arrow outline, tip at the origin, in units of the arrow height
+const ARROW_POLYGON: [(f64, f64); 7] = [
+    (0.0, 0.0),
+    (0.0, 0.842),
+    (0.211, 0.658),
+    (0.368, 0.974),
+    (0.5, 0.921),
+    (0.358, 0.611),
+    (0.605, 0.611),
+];
 
 /// Smoothed cursor position at a given time
 #[derive(Debug, Clone)]
@@ -125,8 +155,11 @@ fn check_cursor_visible(
         .find(|e| matches!(e.event_type, EventType::Click | EventType::Move));
 
     match last_active {
-        Some(event) => (time_ms - event.timestamp_ms) < auto_hide_after_ms,
-        None => false,
+        Some(event) if event.timestamp_ms <= time_ms => {
+            (time_ms - event.timestamp_ms) < auto_hide_after_ms
+        }
+        // The nearest event is still in the future: the cursor hasn't been seen yet
+        _ => false,
     }
 }
 
@@ -149,6 +182,361 @@ fn get_click_highlight(events: &[MouseEvent], time_ms: u64) -> Option<ClickHighl
         })
 }
 
+/// Spotlight strength at `time_ms`, from 0.0 (off) to 1.0, fading in and out
+/// at the edges of each spotlight period
+pub fn get_spotlight_strength(time_ms: u64, events: &[MouseEvent], config: &CursorConfig) -> f64 {
+    match &config.spotlight {
+        SpotlightMode::Off => 0.0,
+        SpotlightMode::Ranges { ranges } => ranges
+            .iter()
+            .map(|range| period_strength(time_ms, range.start_ms, range.end_ms))
+            .fold(0.0, f64::max),
+        SpotlightMode::WhileMoving => match movement_before(time_ms, events) {
+            Some((start, end)) => period_strength(time_ms, start, end + SPOTLIGHT_HOLD_MS),
+            None => 0.0,
+        },
+    }
+}
+
+fn period_strength(time_ms: u64, start_ms: u64, end_ms: u64) -> f64 {
+    if time_ms < start_ms || time_ms >= end_ms {
+        return 0.0;
+    }
+    let fade_in = (time_ms - start_ms) as f64 / SPOTLIGHT_FADE_MS as f64;
+    let fade_out = (end_ms - time_ms) as f64 / SPOTLIGHT_FADE_MS as f64;
+    fade_in.min(fade_out).min(1.0)
+}
+
+/// First and last timestamps of the latest run of moves up to `time_ms`,
+/// where a run breaks at gaps of `SPOTLIGHT_HOLD_MS`. The start is only
+/// searched for as far back as the fade-in needs.
+fn movement_before(time_ms: u64, events: &[MouseEvent]) -> Option<(u64, u64)> {
+    let end = events.partition_point(|e| e.timestamp_ms <= time_ms);
+    let mut moves = events[..end]
+        .iter()
+        .rev()
+        .filter(|e| matches!(e.event_type, EventType::Move))
+        .map(|e| e.timestamp_ms);
+
+    let last = moves.next()?;
+    let mut first = last;
+    for timestamp in moves {
+        if time_ms - first >= SPOTLIGHT_FADE_MS || first - timestamp >= SPOTLIGHT_HOLD_MS {
+            break;
+        }
+        first = timestamp;
+    }
+    Some((first, last))
+}
+
+/// Where the cursor image comes from
+enum SpriteSource {
+    /// Built-in arrow, rasterized at the exact size needed
+    Arrow,
+    /// A user-supplied image with its click point
+    Image {
+        image: RgbaImage,
+        hotspot_x: f64,
+        hotspot_y: f64,
+    },
+}
+
+/// A sprite rendered at one height, with its hotspot in rendered pixels
+struct RenderedSprite {
+    image: RgbaImage,
+    hotspot_x: f64,
+    hotspot_y: f64,
+}
+
+/// The image drawn for the cursor. Renders are cached by whole-pixel
+/// height, which only changes while the zoom moves, and shared by every
+/// worker.
+pub struct CursorSprite {
+    source: SpriteSource,
+    cache: Mutex<HashMap<u32, Arc<RenderedSprite>>>,
+}
+
+impl CursorSprite {
+    pub fn from_config(config: &CursorConfig) -> Result<Self, String> {
+        let source = match &config.custom_image {
+            None => SpriteSource::Arrow,
+            Some(CursorImage { path, hotspot_x, hotspot_y }) => {
+                let image = image::open(path)
+                    .map_err(|e| format!("Failed to load cursor image {}: {}", path, e))?
+                    .to_rgba8();
+                SpriteSource::Image {
+                    image,
+                    hotspot_x: *hotspot_x,
+                    hotspot_y: *hotspot_y,
+                }
+            }
+        };
+        Ok(Self::new(source))
+    }
+
+    fn new(source: SpriteSource) -> Self {
+        Self { source, cache: Mutex::new(HashMap::new()) }
+    }
+
+    /// The sprite at the given height in pixels, rounded to a whole pixel
+    fn render(&self, height: f64) -> Arc<RenderedSprite> {
+        let height = (height.round() as u32).max(1);
+        if let Some(sprite) = self.cache.lock().ok().and_then(|cache| cache.get(&height).cloned()) {
+            return sprite;
+        }
+
+        // Render outside the lock; two workers racing on a new height just
+        // both render it
+        let sprite = Arc::new(self.rasterize(height));
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(height, sprite.clone());
+        }
+        sprite
+    }
+
+    fn rasterize(&self, height: u32) -> RenderedSprite {
+        match &self.source {
+            SpriteSource::Arrow => RenderedSprite {
+                image: rasterize_arrow(height as f64),
+                hotspot_x: 0.0,
+                hotspot_y: 0.0,
+            },
+            SpriteSource::Image { image, hotspot_x, hotspot_y } => {
+                let scale = height as f64 / image.height().max(1) as f64;
+                let w = ((image.width() as f64 * scale).round() as u32).max(1);
+                RenderedSprite {
+                    image: imageops::resize(image, w, height, FilterType::Triangle),
+                    hotspot_x: hotspot_x * scale,
+                    hotspot_y: hotspot_y * scale,
+                }
+            }
+        }
+    }
+}
+
+/// Draw the click highlight and cursor sprite onto a zoomed video frame.
+/// With no `sprite`, only the click highlight is drawn.
+///
+/// `state` is in source coordinates; it is mapped through `viewport` so the
+/// cursor stays on the right spot and grows with the zoom. `pixel_scale`
+/// converts display points to source pixels (2.0 for Retina captures).
+pub fn draw_cursor(
+    frame: &mut RgbaImage,
+    state: &CursorState,
+    viewport: &FrameViewport,
+    sprite: Option<&CursorSprite>,
+    config: &CursorConfig,
+    pixel_scale: f64,
+) {
+    if viewport.width <= 0.0 || viewport.height <= 0.0 {
+        return;
+    }
+    let sx = frame.width() as f64 / viewport.width;
+    let sy = frame.height() as f64 / viewport.height;
+    let to_frame = |x: f64, y: f64| ((x - viewport.x) * sx, (y - viewport.y) * sy);
+
+    if let Some(highlight) = &state.click_highlight {
+        let (cx, cy) = to_frame(highlight.x, highlight.y);
+        let radius = config.highlight_radius as f64 * pixel_scale * sx;
+        draw_click_ring(frame, cx, cy, radius, highlight.progress, &config.highlight_color);
+    }
+
+    let Some(sprite) = sprite.filter(|_| state.visible) else {
+        return;
+    };
+
+    let height = CURSOR_BASE_SIZE * config.scale * pixel_scale * sx;
+    if height < 1.0 {
+        return;
+    }
+    let rendered = sprite.render(height);
+    let (px, py) = to_frame(state.x, state.y);
+    blend_sprite(
+        frame,
+        &rendered.image,
+        (px - rendered.hotspot_x).round() as i64,
+        (py - rendered.hotspot_y).round() as i64,
+    );
+}
+
+/// Darken the frame outside a soft circle around the cursor. `strength`
+/// comes from [`get_spotlight_strength`]; sizes map through `viewport` like
+/// the cursor itself.
+pub fn draw_spotlight(
+    frame: &mut RgbaImage,
+    state: &CursorState,
+    strength: f64,
+    viewport: &FrameViewport,
+    config: &CursorConfig,
+    pixel_scale: f64,
+) {
+    let dim = config.spotlight_dim.clamp(0.0, 1.0) * strength.clamp(0.0, 1.0);
+    if dim <= 0.0 || viewport.width <= 0.0 || viewport.height <= 0.0 {
+        return;
+    }
+    let sx = frame.width() as f64 / viewport.width;
+    let sy = frame.height() as f64 / viewport.height;
+    let cx = (state.x - viewport.x) * sx;
+    let cy = (state.y - viewport.y) * sy;
+    let radius = config.spotlight_radius as f64 * pixel_scale * sx;
+    let feather = (config.spotlight_feather as f64 * pixel_scale * sx).max(1.0);
+
+    for (x, y, pixel) in frame.enumerate_pixels_mut() {
+        let dx = x as f64 + 0.5 - cx;
+        let dy = y as f64 + 0.5 - cy;
+        let t = (((dx * dx + dy * dy).sqrt() - radius) / feather).clamp(0.0, 1.0);
+        if t <= 0.0 {
+            continue;
+        }
+        let factor = 1.0 - dim * t * t * (3.0 - 2.0 * t);
+        for channel in &mut pixel.0[..3] {
+            *channel = (*channel as f64 * factor).round() as u8;
+        }
+    }
+}
+
+/// Draws the spotlight, click highlight and cursor onto the zoomed video
+pub struct CursorLayer {
+    config: CursorConfig,
+    /// `None` when the recording already shows the system cursor
+    sprite: Option<CursorSprite>,
+    /// Mouse events mapped into source pixel coordinates
+    mouse_events: Vec<MouseEvent>,
+    /// Source pixels per display point (2.0 for Retina captures)
+    pixel_scale: f64,
+}
+
+impl CursorLayer {
+    pub fn new(
+        config: &CursorConfig,
+        mouse_events: Vec<MouseEvent>,
+        pixel_scale: f64,
+        draw_sprite: bool,
+    ) -> Result<Self, String> {
+        let sprite = if draw_sprite { Some(CursorSprite::from_config(config)?) } else { None };
+        Ok(Self {
+            config: config.clone(),
+            sprite,
+            mouse_events,
+            pixel_scale,
+        })
+    }
+}
+
+impl Layer for CursorLayer {
+    fn render(&self, surfaces: &mut Surfaces, ctx: &FrameContext) {
+        let state = get_cursor_at_time(ctx.time_ms, &self.mouse_events, &self.config);
+        let spotlight = get_spotlight_strength(ctx.time_ms, &self.mouse_events, &self.config);
+        let frame = &mut surfaces.video;
+        draw_spotlight(frame, &state, spotlight, &ctx.viewport, &self.config, self.pixel_scale);
+        draw_cursor(frame, &state, &ctx.viewport, self.sprite.as_ref(), &self.config, self.pixel_scale);
+    }
+}
+
+/// Expanding, fading ring around a click point
+fn draw_click_ring(
+    frame: &mut RgbaImage,
+    cx: f64,
+    cy: f64,
+    max_radius: f64,
+    progress: f64,
+    color: &str,
+) {
+    let progress = progress.clamp(0.0, 1.0);
+    let eased = 1.0 - (1.0 - progress).powi(3);
+    let radius = max_radius * (0.4 + 0.6 * eased);
+    let thickness = (max_radius * 0.12).max(2.0);
+    let fade = 1.0 - progress;
+
+    let base = parse_hex_color(color);
+    let base_alpha = base[3] as f64 / 255.0;
+
+    let outer = radius + thickness / 2.0 + 1.0;
+    let x0 = (cx - outer).floor().max(0.0) as u32;
+    let y0 = (cy - outer).floor().max(0.0) as u32;
+    let x1 = ((cx + outer).ceil().max(0.0) as u32).min(frame.width());
+    let y1 = ((cy + outer).ceil().max(0.0) as u32).min(frame.height());
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let dx = x as f64 + 0.5 - cx;
+            let dy = y as f64 + 0.5 - cy;
+            let dist = (dx * dx + dy * dy).sqrt();
+
+            // Anti-aliased ring plus a faint fill inside it
+            let ring = (thickness / 2.0 - (dist - radius).abs() + 0.5).clamp(0.0, 1.0);
+            let fill = (radius - dist + 0.5).clamp(0.0, 1.0) * 0.25;
+            let coverage = ring.max(fill) * fade * base_alpha;
+            if coverage <= 0.0 {
+                continue;
+            }
+
+            let fg = Rgba([base[0], base[1], base[2], (coverage * 255.0).round() as u8]);
+            let blended = alpha_blend(frame.get_pixel(x, y), &fg);
+            frame.put_pixel(x, y, blended);
+        }
+    }
+}
+
+/// Rasterize the built-in arrow: black fill with a white outline
+fn rasterize_arrow(height: f64) -> RgbaImage {
+    const SAMPLES: u32 = 4;
+
+    let width = (height * 0.65).ceil() as u32 + 1;
+    let h = height.ceil() as u32 + 1;
+    let outline = (height * 0.07).max(1.0);
+    let points: Vec<(f64, f64)> = ARROW_POLYGON
+        .iter()
+        .map(|(x, y)| (x * height, y * height))
+        .collect();
+
+    RgbaImage::from_fn(width, h, |x, y| {
+        let mut coverage = 0.0f64;
+        let mut shade = 0.0f64;
+        for sy in 0..SAMPLES {
+            for sx in 0..SAMPLES {
+                let px = x as f64 + (sx as f64 + 0.5) / SAMPLES as f64;
+                let py = y as f64 + (sy as f64 + 0.5) / SAMPLES as f64;
+                if !point_in_polygon(px, py, &points) {
+                    continue;
+                }
+                coverage += 1.0;
+                if distance_to_outline(px, py, &points) < outline {
+                    shade += 1.0;
+                }
+            }
+        }
+        if coverage == 0.0 {
+            return Rgba([0, 0, 0, 0]);
+        }
+        let v = (shade / coverage * 255.0).round() as u8;
+        let alpha = coverage / (SAMPLES * SAMPLES) as f64;
+        Rgba([v, v, v, (alpha * 255.0).round() as u8])
+    })
+}
+
+fn distance_to_outline(x: f64, y: f64, points: &[(f64, f64)]) -> f64 {
+    (0..points.len())
+        .map(|i| distance_to_segment(x, y, points[i], points[(i + 1) % points.len()]))
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Alpha-blend a sprite onto the frame, clipping at the edges
+fn blend_sprite(frame: &mut RgbaImage, sprite: &RgbaImage, left: i64, top: i64) {
+    for (x, y, pixel) in sprite.enumerate_pixels() {
+        if pixel[3] == 0 {
+            continue;
+        }
+        let px = left + x as i64;
+        let py = top + y as i64;
+        if px < 0 || py < 0 || px >= frame.width() as i64 || py >= frame.height() as i64 {
+            continue;
+        }
+        let blended = alpha_blend(frame.get_pixel(px as u32, py as u32), pixel);
+        frame.put_pixel(px as u32, py as u32, blended);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -240,4 +628,162 @@ mod tests {
         assert!((state.x - 500.0).abs() < 1e-10);
         assert!((state.y - 500.0).abs() < 1e-10);
     }
+
+    #[test]
+    fn test_cursor_hidden_before_first_event() {
+        let events = vec![make_event(500, 100.0, 100.0, EventType::Move)];
+        let state = get_cursor_at_time(0, &events, &default_config());
+        assert!(!state.visible);
+    }
+
+    fn full_viewport(width: f64, height: f64) -> FrameViewport {
+        FrameViewport {
+            x: 0.0,
+            y: 0.0,
+            width,
+            height,
+            zoom: 1.0,
+            center_x: width / 2.0,
+            center_y: height / 2.0,
+        }
+    }
+
+    #[test]
+    fn test_rasterize_arrow_has_tip_at_origin() {
+        let arrow = rasterize_arrow(48.0);
+        assert!(arrow.get_pixel(1, 4)[3] > 0, "Arrow should cover its left edge near the tip");
+        assert_eq!(arrow.get_pixel(arrow.width() - 1, 0)[3], 0);
+    }
+
+    #[test]
+    fn test_draw_cursor_at_position() {
+        let mut frame = RgbaImage::from_pixel(200, 200, Rgba([0, 0, 255, 255]));
+        let state = CursorState { x: 100.0, y: 100.0, visible: true, click_highlight: None };
+        let config = default_config();
+        draw_cursor(&mut frame, &state, &full_viewport(200.0, 200.0), Some(&CursorSprite::new(SpriteSource::Arrow)), &config, 1.0);
+
+        // Just below-right of the hotspot is inside the arrow
+        assert_ne!(*frame.get_pixel(102, 110), Rgba([0, 0, 255, 255]));
+        // Above-left of the hotspot is untouched
+        assert_eq!(*frame.get_pixel(95, 95), Rgba([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn test_draw_cursor_follows_zoom_viewport() {
+        let mut frame = RgbaImage::from_pixel(200, 200, Rgba([0, 0, 255, 255]));
+        // 2x zoom on the bottom-right quadrant; cursor at source (150, 150)
+        let viewport = FrameViewport {
+            x: 100.0,
+            y: 100.0,
+            width: 100.0,
+            height: 100.0,
+            zoom: 2.0,
+            center_x: 150.0,
+            center_y: 150.0,
+        };
+        let state = CursorState { x: 150.0, y: 150.0, visible: true, click_highlight: None };
+        draw_cursor(&mut frame, &state, &viewport, Some(&CursorSprite::new(SpriteSource::Arrow)), &default_config(), 1.0);
+
+        // Maps to frame (100, 100) and is drawn at twice the size (48px tall)
+        assert_ne!(*frame.get_pixel(104, 125), Rgba([0, 0, 255, 255]));
+        assert_eq!(*frame.get_pixel(98, 98), Rgba([0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn test_draw_cursor_hidden_draws_nothing() {
+        let mut frame = RgbaImage::from_pixel(100, 100, Rgba([0, 0, 255, 255]));
+        let state = CursorState { x: 50.0, y: 50.0, visible: false, click_highlight: None };
+        draw_cursor(&mut frame, &state, &full_viewport(100.0, 100.0), Some(&CursorSprite::new(SpriteSource::Arrow)), &default_config(), 1.0);
+        assert!(frame.pixels().all(|p| *p == Rgba([0, 0, 255, 255])));
+    }
+
+    #[test]
+    fn test_draw_cursor_without_sprite_only_highlights() {
+        let background = Rgba([0, 0, 255, 255]);
+        let viewport = full_viewport(200.0, 200.0);
+        let mut state = CursorState { x: 50.0, y: 50.0, visible: true, click_highlight: None };
+        let mut frame = RgbaImage::from_pixel(200, 200, background);
+        draw_cursor(&mut frame, &state, &viewport, None, &default_config(), 1.0);
+        assert!(frame.pixels().all(|p| *p == background));
+
+        state.click_highlight = Some(ClickHighlight { progress: 0.2, x: 100.0, y: 100.0 });
+        draw_cursor(&mut frame, &state, &viewport, None, &default_config(), 1.0);
+        assert!(frame.pixels().any(|p| *p != background));
+    }
+
+    #[test]
+    fn test_click_ring_fades_out() {
+        let state_at = |progress| CursorState {
+            x: 0.0,
+            y: 0.0,
+            visible: false,
+            click_highlight: Some(ClickHighlight { progress, x: 100.0, y: 100.0 }),
+        };
+        let config = default_config();
+        let viewport = full_viewport(200.0, 200.0);
+
+        let mut early = RgbaImage::from_pixel(200, 200, Rgba([0, 0, 0, 255]));
+        draw_cursor(&mut early, &state_at(0.2), &viewport, Some(&CursorSprite::new(SpriteSource::Arrow)), &config, 1.0);
+        let mut late = RgbaImage::from_pixel(200, 200, Rgba([0, 0, 0, 255]));
+        draw_cursor(&mut late, &state_at(0.9), &viewport, Some(&CursorSprite::new(SpriteSource::Arrow)), &config, 1.0);
+
+        let brightness = |img: &RgbaImage| img.pixels().map(|p| p[0] as u64).sum::<u64>();
+        assert!(brightness(&early) > brightness(&late));
+        assert!(brightness(&late) > 0);
+    }
+
+    #[test]
+    fn test_spotlight_ranges_fade() {
+        use crate::models::effects::SpotlightRange;
+
+        let config = CursorConfig {
+            spotlight: SpotlightMode::Ranges { ranges: vec![SpotlightRange { start_ms: 1000, end_ms: 3000 }] },
+            ..default_config()
+        };
+        assert_eq!(get_spotlight_strength(999, &[], &config), 0.0);
+        assert!((get_spotlight_strength(1125, &[], &config) - 0.5).abs() < 1e-10);
+        assert_eq!(get_spotlight_strength(2000, &[], &config), 1.0);
+        assert_eq!(get_spotlight_strength(3000, &[], &config), 0.0);
+        assert_eq!(get_spotlight_strength(2000, &[], &default_config()), 0.0);
+    }
+
+    #[test]
+    fn test_spotlight_while_moving() {
+        let config = CursorConfig { spotlight: SpotlightMode::WhileMoving, ..default_config() };
+        let events: Vec<_> = (0..10)
+            .map(|i| make_event(1000 + i * 100, i as f64, 0.0, EventType::Move))
+            .chain([make_event(1950, 9.0, 0.0, EventType::Click)])
+            .collect();
+
+        assert_eq!(get_spotlight_strength(500, &events, &config), 0.0);
+        assert!(get_spotlight_strength(1100, &events, &config) < 1.0);
+        assert_eq!(get_spotlight_strength(1500, &events, &config), 1.0);
+        // Last move at 1900 holds until 2400, fading out over the last 250ms
+        assert_eq!(get_spotlight_strength(2100, &events, &config), 1.0);
+        assert!((get_spotlight_strength(2275, &events, &config) - 0.5).abs() < 1e-10);
+        assert_eq!(get_spotlight_strength(2400, &events, &config), 0.0);
+    }
+
+    #[test]
+    fn test_draw_spotlight_dims_surroundings() {
+        let gray = Rgba([200, 200, 200, 255]);
+        let mut frame = RgbaImage::from_pixel(400, 400, gray);
+        let state = CursorState { x: 100.0, y: 100.0, visible: true, click_highlight: None };
+        let config = CursorConfig { spotlight_radius: 50, spotlight_feather: 50, spotlight_dim: 0.5, ..default_config() };
+        draw_spotlight(&mut frame, &state, 1.0, &full_viewport(400.0, 400.0), &config, 1.0);
+
+        assert_eq!(*frame.get_pixel(100, 100), gray);
+        assert_eq!(*frame.get_pixel(140, 100), gray);
+        assert_eq!(*frame.get_pixel(399, 399), Rgba([100, 100, 100, 255]));
+        let edge = frame.get_pixel(175, 100)[0];
+        assert!(edge > 100 && edge < 200, "got {}", edge);
+    }
+
+    #[test]
+    fn test_sprite_renders_are_cached_by_height() {
+        let sprite = CursorSprite::new(SpriteSource::Arrow);
+        let first = sprite.render(24.2);
+        assert!(Arc::ptr_eq(&first, &sprite.render(23.8)));
+        assert!(!Arc::ptr_eq(&first, &sprite.render(48.0)));
+    }
 }
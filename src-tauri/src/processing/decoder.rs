@@ -0,0 +1,248 @@
+use std::io::{ErrorKind, Read};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::thread::JoinHandle;
+
+use image::RgbaImage;
+
+use crate::processing::encoder::{find_ffmpeg, find_ffprobe};
+
+/// Streams decoded RGBA frames out of an FFmpeg subprocess.
+///
+/// The source is resampled to a constant frame rate, so the n-th frame
+/// returned always belongs at `n * 1000 / fps` milliseconds. This keeps
+/// zoom and cursor timing in sync with the recorded events even when
+/// the capture itself was variable frame rate.
+pub struct FrameDecoder {
+    child: Child,
+    stdout: ChildStdout,
+    stderr: Option<JoinHandle<String>>,
+    width: u32,
+    height: u32,
+    fps: f64,
+    frames_read: u64,
+}
+
+impl FrameDecoder {
+    /// Start decoding `input_path` at its native resolution
+    pub fn open(input_path: &str, fps: f64) -> Result<Self, String> {
+        Self::spawn(input_path, fps, 0, None)
+    }
+
+    /// Start decoding `input_path` at its native resolution from `start_ms`.
+    /// Frame timestamps count from `start_ms`.
+    pub fn open_at(input_path: &str, fps: f64, start_ms: u64) -> Result<Self, String> {
+        Self::spawn(input_path, fps, start_ms, None)
+    }
+
+    /// Start decoding `input_path` from `start_ms`, downscaled to at most
+    /// `max_width` pixels wide
+    pub fn open_scaled(input_path: &str, fps: f64, start_ms: u64, max_width: u32) -> Result<Self, String> {
+        Self::spawn(input_path, fps, start_ms, Some(max_width))
+    }
+
+    fn spawn(input_path: &str, fps: f64, start_ms: u64, max_width: Option<u32>) -> Result<Self, String> {
+        let ffmpeg_path = find_ffmpeg()?;
+        let (source_width, source_height) = probe_dimensions(input_path)?;
+        let (width, height) = match max_width {
+            Some(max_width) => scaled_dimensions(source_width, source_height, max_width),
+            None => (source_width, source_height),
+        };
+
+        let mut filters = format!("fps={}", fps);
+        if (width, height) != (source_width, source_height) {
+            filters.push_str(&format!(",scale={}:{}:flags=area", width, height));
+        }
+
+        let mut cmd = Command::new(ffmpeg_path);
+        cmd.arg("-v").arg("error");
+        if start_ms > 0 {
+            cmd.arg("-ss").arg(format!("{:.3}", start_ms as f64 / 1000.0));
+        }
+        cmd.arg("-i")
+            .arg(input_path)
+            .arg("-vf")
+            .arg(filters)
+            .arg("-f")
+            .arg("rawvideo")
+            .arg("-pix_fmt")
+            .arg("rgba")
+            .arg("-")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        log::info!("Running FFmpeg decoder: {:?}", cmd);
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("Failed to start FFmpeg decoder: {}", e))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or("Failed to open FFmpeg decoder output")?;
+        let stderr = drain_stderr(&mut child);
+
+        Ok(Self {
+            child,
+            stdout,
+            stderr,
+            width,
+            height,
+            fps,
+            frames_read: 0,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Read the next frame along with its presentation timestamp (ms).
+    /// Returns `None` once the source is exhausted.
+    pub fn next_frame(&mut self) -> Result<Option<(u64, RgbaImage)>, String> {
+        let mut buffer = vec![0u8; self.width as usize * self.height as usize * 4];
+
+        match self.stdout.read_exact(&mut buffer) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(format!("Failed to read decoded frame: {}", e)),
+        }
+
+        let time_ms = (self.frames_read as f64 * 1000.0 / self.fps).round() as u64;
+        self.frames_read += 1;
+
+        let frame = RgbaImage::from_raw(self.width, self.height, buffer)
+            .ok_or("Decoded frame has unexpected size")?;
+        Ok(Some((time_ms, frame)))
+    }
+
+    /// Wait for FFmpeg to exit and surface any decoding error
+    pub fn finish(mut self) -> Result<(), String> {
+        let status = self
+            .child
+            .wait()
+            .map_err(|e| format!("Failed to wait for FFmpeg decoder: {}", e))?;
+        let stderr = join_stderr(self.stderr.take());
+
+        if !status.success() {
+            return Err(format!("FFmpeg decoding failed: {}", stderr.trim()));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FrameDecoder {
+    fn drop(&mut self) {
+        // No-op if the process already exited; stops FFmpeg if export bailed early
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Collect a child's stderr on a background thread, so FFmpeg never stalls
+/// on a full pipe however much it logs during a long run
+pub fn drain_stderr(child: &mut Child) -> Option<JoinHandle<String>> {
+    let mut pipe = child.stderr.take()?;
+    Some(std::thread::spawn(move || {
+        let mut bytes = Vec::new();
+        pipe.read_to_end(&mut bytes).ok();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }))
+}
+
+/// Everything the child wrote to stderr; call once it has exited
+pub fn join_stderr(stderr: Option<JoinHandle<String>>) -> String {
+    stderr.and_then(|handle| handle.join().ok()).unwrap_or_default()
+}
+
+/// Get the pixel dimensions of the first video stream
+pub fn probe_dimensions(video_path: &str) -> Result<(u32, u32), String> {
+    let ffprobe = find_ffprobe()?;
+
+    let output = Command::new(ffprobe)
+        .args([
+            "-v", "error",
+            "-select_streams", "v:0",
+            "-show_entries", "stream=width,height",
+            "-of", "csv=s=x:p=0",
+            video_path,
+        ])
+        .output()
+        .map_err(|e| format!("Failed to run ffprobe: {}", e))?;
+
+    if !output.status.success() {
+        return Err("Failed to get video dimensions".to_string());
+    }
+
+    parse_dimensions(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| "Failed to parse video dimensions".to_string())
+}
+
+/// Dimensions no wider than `max_width`, keeping the aspect ratio
+pub fn scaled_dimensions(width: u32, height: u32, max_width: u32) -> (u32, u32) {
+    if width <= max_width || max_width == 0 {
+        return (width, height);
+    }
+    let scaled_height = (height as f64 * max_width as f64 / width as f64).round().max(1.0) as u32;
+    (max_width, scaled_height)
+}
+
+fn parse_dimensions(output: &str) -> Option<(u32, u32)> {
+    let line = output.lines().next()?.trim();
+    let (w, h) = line.split_once('x')?;
+    let width = w.trim().parse().ok()?;
+    let height = h.trim().trim_end_matches('x').parse().ok()?;
+    if width == 0 || height == 0 {
+        return None;
+    }
+    Some((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drain_stderr_keeps_chatty_child_running() {
+        // Far more than a pipe buffer holds, so the child would block on
+        // its stderr if nothing read it while we wait
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg("head -c 1000000 /dev/zero | tr '\\0' x >&2")
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stderr = drain_stderr(&mut child);
+        assert!(child.wait().unwrap().success());
+        assert_eq!(join_stderr(stderr).len(), 1_000_000);
+    }
+
+    #[test]
+    fn test_parse_dimensions() {
+        assert_eq!(parse_dimensions("3024x1964\n"), Some((3024, 1964)));
+    }
+
+    #[test]
+    fn test_parse_dimensions_trailing_separator() {
+        // Some ffprobe builds emit a trailing separator for side data
+        assert_eq!(parse_dimensions("1920x1080x\n"), Some((1920, 1080)));
+    }
+
+    #[test]
+    fn test_scaled_dimensions() {
+        assert_eq!(scaled_dimensions(1920, 1080, 640), (640, 360));
+        assert_eq!(scaled_dimensions(1280, 720, 1920), (1280, 720));
+        assert_eq!(scaled_dimensions(1000, 333, 500), (500, 167));
+    }
+
+    #[test]
+    fn test_parse_dimensions_malformed() {
+        assert_eq!(parse_dimensions(""), None);
+        assert_eq!(parse_dimensions("N/A"), None);
+        assert_eq!(parse_dimensions("0x1080"), None);
+    }
+}
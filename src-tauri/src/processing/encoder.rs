@@ -1,78 +1,176 @@
+use std::io::Write;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::thread::JoinHandle;
 
-use crate::models::effects::{ExportConfig, ExportFormat};
+use image::RgbaImage;
+
+use crate::models::effects::{Background, ExportConfig, ExportFormat};
+use crate::models::events::RecordedEvents;
 use crate::models::project::Project;
+use crate::processing::compositor::Compositor;
+use crate::processing::decoder::{self, FrameDecoder};
+use crate::processing::overlay::OverlayReader;
+use crate::processing::pipeline;
+
+/// GIF and APNG exports are rendered at a reduced frame rate to keep file size sane
+const ANIMATION_FPS: f64 = 15.0;
+/// Maximum GIF width in pixels
+const GIF_MAX_WIDTH: u32 = 800;
 
 /// Export a project using FFmpeg CLI.
-/// For the MVP, we use FFmpeg as a subprocess for encoding.
-/// This approach is simpler and avoids FFmpeg linking issues.
+/// FFmpeg runs as two subprocesses, which avoids FFmpeg linking issues.
 ///
 /// The pipeline:
-/// 1. Read source video frames
-/// 2. Apply zoom/crop via FFmpeg's zoompan filter
-/// 3. Composite background (overlay filter)
-/// 4. Encode to output format
+/// 1. Decode source frames (and overlay frames, if any) to raw RGBA
+/// 2. Compose each frame through the compositor's layer stack
+///    at its presentation timestamp, spread across a bounded worker pool
+/// 3. Stream the composed RGBA frames, in order, into the encoder's stdin
+/// 4. Encode to the output format
 pub fn export_project(
     project: &Project,
+    events: &RecordedEvents,
     config: &ExportConfig,
     on_progress: impl Fn(f64) + Send + 'static,
 ) -> Result<String, String> {
+    if matches!(project.frame_style.background, Background::Transparent) && !config.format.supports_alpha() {
+        return Err("A transparent background needs a WebM, ProRes or APNG export".to_string());
+    }
+
     let ffmpeg_path = find_ffmpeg()?;
     let output_path = &config.output_path;
-    let (out_w, out_h) = config.resolution.dimensions();
 
     // Ensure output directory exists
     if let Some(parent) = Path::new(output_path).parent() {
         std::fs::create_dir_all(parent).ok();
     }
 
-    match config.format {
-        ExportFormat::Mp4 => export_mp4(
-            &ffmpeg_path,
-            &project.video_path,
-            output_path,
-            out_w,
-            out_h,
-            config.quality,
-            on_progress,
-        ),
-        ExportFormat::Gif => export_gif(
-            &ffmpeg_path,
-            &project.video_path,
-            output_path,
-            out_w,
-            out_h,
-            on_progress,
-        ),
+    let fps = match config.format {
+        ExportFormat::Mp4 | ExportFormat::WebM | ExportFormat::ProRes => project.fps,
+        ExportFormat::Gif | ExportFormat::Apng => ANIMATION_FPS,
+    };
+
+    let mut decoder = FrameDecoder::open(&project.video_path, fps)?;
+    let mut overlay = match &project.overlay {
+        Some(track) => Some(OverlayReader::open(track, fps, decoder.width(), 0)?),
+        None => None,
+    };
+    let overlay_size = overlay.as_ref().map(|reader| (reader.width(), reader.height()));
+    let compositor = Compositor::new(project, events, decoder.width(), decoder.height(), fps, overlay_size)?;
+
+    let canvas = (compositor.output_width, compositor.output_height);
+    let cmd = match config.format {
+        ExportFormat::Mp4 => mp4_command(&ffmpeg_path, &project.video_path, canvas, fps, config),
+        ExportFormat::Gif => gif_command(&ffmpeg_path, canvas, fps, config),
+        ExportFormat::WebM => webm_command(&ffmpeg_path, &project.video_path, canvas, fps, config),
+        ExportFormat::ProRes => prores_command(&ffmpeg_path, &project.video_path, canvas, fps, config),
+        ExportFormat::Apng => apng_command(&ffmpeg_path, canvas, fps, config),
+    };
+    let mut encoder = FrameEncoder::spawn(cmd)?;
+
+    let duration_ms = if project.duration_ms > 0 {
+        project.duration_ms
+    } else {
+        get_video_duration_ms(&project.video_path).unwrap_or(0)
+    };
+    let total_frames = (duration_ms as f64 / 1000.0 * fps).ceil().max(1.0);
+
+    let workers = config.workers();
+    let overlay_pixels = overlay.as_ref().map_or(0, |o| o.width() as usize * o.height() as usize);
+    let bytes_per_frame = (decoder.width() as usize * decoder.height() as usize
+        + overlay_pixels
+        + canvas.0 as usize * canvas.1 as usize)
+        * 4;
+    let max_in_flight = pipeline::max_frames_in_flight(workers, bytes_per_frame);
+    log::info!("Compositing with {} workers, {} frames in flight", workers, max_in_flight);
+
+    let mut frames_written = 0u64;
+    let result = pipeline::run(
+        || {
+            let Some((time_ms, frame)) = decoder.next_frame()? else {
+                return Ok(None);
+            };
+            let overlay_frame = match overlay.as_mut() {
+                Some(reader) => reader.frame_at(time_ms)?,
+                None => None,
+            };
+            Ok(Some((time_ms, (frame, overlay_frame))))
+        },
+        |(frame, overlay_frame), time_ms| compositor.compose_frame(frame, overlay_frame.as_ref(), time_ms),
+        |composed| {
+            encoder.write_frame(&composed)?;
+            frames_written += 1;
+            on_progress((frames_written as f64 / total_frames).min(0.99));
+            Ok(())
+        },
+        workers,
+        max_in_flight,
+    );
+    let finished = match result {
+        // A broken pipe means the encoder died; its stderr explains why
+        Err(e) => encoder.finish().and(Err(e)),
+        Ok(_) => decoder.finish().and_then(|()| encoder.finish()),
+    };
+    if let Err(e) = finished {
+        // Don't leave a truncated file that looks like a finished export
+        std::fs::remove_file(output_path).ok();
+        return Err(e);
     }
+    on_progress(1.0);
+
+    Ok(output_path.to_string())
+}
+
+/// Input arguments for raw RGBA frames piped over stdin
+fn raw_input_args(cmd: &mut Command, canvas: (u32, u32), fps: f64) {
+    cmd.arg("-f")
+        .arg("rawvideo")
+        .arg("-pix_fmt")
+        .arg("rgba")
+        .arg("-s")
+        .arg(format!("{}x{}", canvas.0, canvas.1))
+        .arg("-r")
+        .arg(fps.to_string())
+        .arg("-i")
+        .arg("-");
+}
+
+/// Take video from the piped frames and audio, if there is any, from the recording
+fn audio_input_args(cmd: &mut Command, audio_source: &str) {
+    cmd.arg("-i")
+        .arg(audio_source)
+        .arg("-map")
+        .arg("0:v")
+        .arg("-map")
+        .arg("1:a?");
 }
 
-fn export_mp4(
+fn mp4_command(
     ffmpeg_path: &str,
-    input_path: &str,
-    output_path: &str,
-    width: u32,
-    height: u32,
-    quality: f64,
-    _on_progress: impl Fn(f64) + Send + 'static,
-) -> Result<String, String> {
+    audio_source: &str,
+    canvas: (u32, u32),
+    fps: f64,
+    config: &ExportConfig,
+) -> Command {
+    // Already even and matching the canvas aspect, so no padding is needed
+    let (width, height) = config.resolution.fit_canvas(canvas.0, canvas.1);
+
     // CRF value: lower = better quality, range 0-51
     // Map our 0-1 quality to CRF 28-18 (reasonable range)
-    let crf = (28.0 - quality * 10.0) as u32;
-
-    // Ensure even dimensions
-    let width = width - (width % 2);
-    let height = height - (height % 2);
+    let crf = (28.0 - config.quality * 10.0) as u32;
 
     let mut cmd = Command::new(ffmpeg_path);
     cmd.arg("-y") // overwrite output
-        .arg("-i")
-        .arg(input_path)
-        .arg("-vf")
-        .arg(format!("scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:(ow-iw)/2:(oh-ih)/2", width, height, width, height))
+        .arg("-v")
+        .arg("error");
+    raw_input_args(&mut cmd, canvas, fps);
+    audio_input_args(&mut cmd, audio_source);
+    cmd.arg("-vf")
+        .arg(format!("scale={}:{}:flags=lanczos", width, height))
         .arg("-c:v")
         .arg("libx264")
+        .arg("-pix_fmt")
+        .arg("yuv420p")
         .arg("-preset")
         .arg("medium")
         .arg("-crf")
@@ -81,87 +179,193 @@ fn export_mp4(
         .arg("aac")
         .arg("-b:a")
         .arg("128k")
+        .arg("-shortest")
         .arg("-movflags")
         .arg("+faststart")
-        .arg(output_path);
+        .arg(&config.output_path);
+    cmd
+}
 
-    log::info!("Running FFmpeg: {:?}", cmd);
+fn gif_command(
+    ffmpeg_path: &str,
+    canvas: (u32, u32),
+    fps: f64,
+    config: &ExportConfig,
+) -> Command {
+    let (width, _) = config.resolution.fit_canvas(canvas.0, canvas.1);
 
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to run FFmpeg: {}", e))?;
+    // Single-pass palette generation: split the stream so the palette is
+    // built from the same frames it is applied to
+    let mut cmd = Command::new(ffmpeg_path);
+    cmd.arg("-y").arg("-v").arg("error");
+    raw_input_args(&mut cmd, canvas, fps);
+    cmd.arg("-lavfi")
+        .arg(format!(
+            "scale={}:-1:flags=lanczos,split[a][b];[a]palettegen[p];[b][p]paletteuse",
+            width.min(GIF_MAX_WIDTH)
+        ))
+        .arg(&config.output_path);
+    cmd
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("FFmpeg export failed: {}", stderr));
-    }
+/// VP9 in WebM, keeping the alpha channel
+fn webm_command(
+    ffmpeg_path: &str,
+    audio_source: &str,
+    canvas: (u32, u32),
+    fps: f64,
+    config: &ExportConfig,
+) -> Command {
+    let (width, height) = config.resolution.fit_canvas(canvas.0, canvas.1);
 
-    Ok(output_path.to_string())
+    // VP9's CRF scale runs 0-63; map our 0-1 quality to CRF 40-20
+    let crf = (40.0 - config.quality * 20.0) as u32;
+
+    let mut cmd = Command::new(ffmpeg_path);
+    cmd.arg("-y").arg("-v").arg("error");
+    raw_input_args(&mut cmd, canvas, fps);
+    audio_input_args(&mut cmd, audio_source);
+    cmd.arg("-vf")
+        .arg(format!("scale={}:{}:flags=lanczos", width, height))
+        .arg("-c:v")
+        .arg("libvpx-vp9")
+        .arg("-pix_fmt")
+        .arg("yuva420p")
+        .arg("-crf")
+        .arg(crf.to_string())
+        .arg("-b:v")
+        .arg("0")
+        // Alternate reference frames are not supported with alpha
+        .arg("-auto-alt-ref")
+        .arg("0")
+        .arg("-row-mt")
+        .arg("1")
+        .arg("-c:a")
+        .arg("libopus")
+        .arg("-b:a")
+        .arg("128k")
+        .arg("-shortest")
+        .arg(&config.output_path);
+    cmd
 }
 
-fn export_gif(
+/// ProRes 4444 in a QuickTime .mov, keeping the alpha channel. ProRes is
+/// an editing format with a fixed quality per profile, so `quality` is unused.
+fn prores_command(
     ffmpeg_path: &str,
-    input_path: &str,
-    output_path: &str,
-    width: u32,
-    _height: u32,
-    _on_progress: impl Fn(f64) + Send + 'static,
-) -> Result<String, String> {
-    // Two-pass GIF encoding for better quality
-    let palette_path = format!("{}.palette.png", output_path);
+    audio_source: &str,
+    canvas: (u32, u32),
+    fps: f64,
+    config: &ExportConfig,
+) -> Command {
+    let (width, height) = config.resolution.fit_canvas(canvas.0, canvas.1);
 
-    // Pass 1: Generate palette
-    let mut cmd1 = Command::new(ffmpeg_path);
-    cmd1.arg("-y")
-        .arg("-i")
-        .arg(input_path)
-        .arg("-vf")
-        .arg(format!(
-            "fps=15,scale={}:-1:flags=lanczos,palettegen",
-            width.min(800)
-        ))
-        .arg(&palette_path);
+    let mut cmd = Command::new(ffmpeg_path);
+    cmd.arg("-y").arg("-v").arg("error");
+    raw_input_args(&mut cmd, canvas, fps);
+    audio_input_args(&mut cmd, audio_source);
+    cmd.arg("-vf")
+        .arg(format!("scale={}:{}:flags=lanczos", width, height))
+        .arg("-c:v")
+        .arg("prores_ks")
+        .arg("-profile:v")
+        .arg("4444")
+        .arg("-pix_fmt")
+        .arg("yuva444p10le")
+        .arg("-c:a")
+        .arg("pcm_s16le")
+        .arg("-shortest")
+        .arg(&config.output_path);
+    cmd
+}
 
-    let output1 = cmd1
-        .output()
-        .map_err(|e| format!("FFmpeg palette generation failed: {}", e))?;
+/// Looping animated PNG with full alpha
+fn apng_command(
+    ffmpeg_path: &str,
+    canvas: (u32, u32),
+    fps: f64,
+    config: &ExportConfig,
+) -> Command {
+    let (width, height) = config.resolution.fit_canvas(canvas.0, canvas.1);
+
+    let mut cmd = Command::new(ffmpeg_path);
+    cmd.arg("-y").arg("-v").arg("error");
+    raw_input_args(&mut cmd, canvas, fps);
+    cmd.arg("-vf")
+        .arg(format!("scale={}:{}:flags=lanczos", width, height))
+        .arg("-c:v")
+        .arg("apng")
+        .arg("-pix_fmt")
+        .arg("rgba")
+        .arg("-plays")
+        .arg("0")
+        .arg("-f")
+        .arg("apng")
+        .arg(&config.output_path);
+    cmd
+}
+
+/// An FFmpeg subprocess that encodes raw RGBA frames written to its stdin
+struct FrameEncoder {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stderr: Option<JoinHandle<String>>,
+}
 
-    if !output1.status.success() {
-        let stderr = String::from_utf8_lossy(&output1.stderr);
-        return Err(format!("FFmpeg palette generation failed: {}", stderr));
+impl FrameEncoder {
+    fn spawn(mut cmd: Command) -> Result<Self, String> {
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+
+        log::info!("Running FFmpeg encoder: {:?}", cmd);
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("Failed to run FFmpeg: {}", e))?;
+        let stdin = child.stdin.take();
+        let stderr = decoder::drain_stderr(&mut child);
+
+        Ok(Self { child, stdin, stderr })
     }
 
-    // Pass 2: Generate GIF using palette
-    let mut cmd2 = Command::new(ffmpeg_path);
-    cmd2.arg("-y")
-        .arg("-i")
-        .arg(input_path)
-        .arg("-i")
-        .arg(&palette_path)
-        .arg("-lavfi")
-        .arg(format!(
-            "fps=15,scale={}:-1:flags=lanczos[x];[x][1:v]paletteuse",
-            width.min(800)
-        ))
-        .arg(output_path);
+    fn write_frame(&mut self, frame: &RgbaImage) -> Result<(), String> {
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or("FFmpeg encoder input already closed")?;
+        stdin
+            .write_all(frame.as_raw())
+            .map_err(|e| format!("Failed to write frame to FFmpeg: {}", e))
+    }
 
-    let output2 = cmd2
-        .output()
-        .map_err(|e| format!("FFmpeg GIF encoding failed: {}", e))?;
+    /// Close the input stream and wait for encoding to complete
+    fn finish(mut self) -> Result<(), String> {
+        // Dropping stdin signals EOF to FFmpeg
+        self.stdin = None;
 
-    // Clean up palette file
-    std::fs::remove_file(&palette_path).ok();
+        let status = self
+            .child
+            .wait()
+            .map_err(|e| format!("Failed to wait for FFmpeg: {}", e))?;
+        let stderr = decoder::join_stderr(self.stderr.take());
 
-    if !output2.status.success() {
-        let stderr = String::from_utf8_lossy(&output2.stderr);
-        return Err(format!("FFmpeg GIF encoding failed: {}", stderr));
+        if !status.success() {
+            return Err(format!("FFmpeg export failed: {}", stderr.trim()));
+        }
+        Ok(())
     }
+}
 
-    Ok(output_path.to_string())
+impl Drop for FrameEncoder {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
 }
 
 /// Find FFmpeg binary on the system
-fn find_ffmpeg() -> Result<String, String> {
+pub fn find_ffmpeg() -> Result<String, String> {
     // Check common locations
     let candidates = [
         "ffmpeg",
@@ -221,7 +425,7 @@ pub fn get_video_duration_ms(video_path: &str) -> Result<u64, String> {
     Ok((duration_secs * 1000.0) as u64)
 }
 
-fn find_ffprobe() -> Result<String, String> {
+pub fn find_ffprobe() -> Result<String, String> {
     let candidates = [
         "ffprobe",
         "/opt/homebrew/bin/ffprobe",
@@ -242,3 +446,98 @@ fn find_ffprobe() -> Result<String, String> {
 
     Err("ffprobe not found. Please install FFmpeg: brew install ffmpeg".to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::effects::ExportResolution;
+
+    fn args_of(cmd: &Command) -> Vec<String> {
+        cmd.get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_mp4_command_reads_raw_frames_at_canvas_size() {
+        let config = ExportConfig {
+            output_path: "/tmp/out.mp4".to_string(),
+            ..ExportConfig::default()
+        };
+        let args = args_of(&mp4_command("ffmpeg", "/tmp/in.mp4", (2048, 1208), 60.0, &config));
+
+        let size_idx = args.iter().position(|a| a == "-s").unwrap();
+        assert_eq!(args[size_idx + 1], "2048x1208");
+        assert!(args.windows(2).any(|w| w[0] == "-i" && w[1] == "-"));
+        assert_eq!(args.last().unwrap(), "/tmp/out.mp4");
+    }
+
+    #[test]
+    fn test_mp4_command_forces_even_dimensions() {
+        let config = ExportConfig {
+            resolution: ExportResolution::Custom { width: 801, height: 601 },
+            ..ExportConfig::default()
+        };
+        let args = args_of(&mp4_command("ffmpeg", "/tmp/in.mp4", (800, 600), 30.0, &config));
+        assert!(args.iter().any(|a| a.starts_with("scale=800:600:")));
+    }
+
+    #[test]
+    fn test_mp4_command_scales_vertical_canvas() {
+        let config = ExportConfig::default();
+        let args = args_of(&mp4_command("ffmpeg", "/tmp/in.mp4", (2000, 3556), 30.0, &config));
+        assert!(args.iter().any(|a| a.starts_with("scale=1080:1920:")));
+    }
+
+    #[test]
+    fn test_gif_command_caps_width() {
+        let config = ExportConfig {
+            format: ExportFormat::Gif,
+            resolution: ExportResolution::R4k,
+            ..ExportConfig::default()
+        };
+        let args = args_of(&gif_command("ffmpeg", (1920, 1080), ANIMATION_FPS, &config));
+        assert!(args.iter().any(|a| a.starts_with("scale=800:-1")));
+    }
+
+    /// Value of the last use of `flag`, i.e. the output option rather than the input one
+    fn arg_after<'a>(args: &'a [String], flag: &str) -> &'a str {
+        let idx = args.iter().rposition(|a| a == flag).unwrap();
+        &args[idx + 1]
+    }
+
+    #[test]
+    fn test_alpha_formats_keep_alpha() {
+        let config = ExportConfig::default();
+        let webm = args_of(&webm_command("ffmpeg", "/tmp/in.mp4", (1920, 1080), 60.0, &config));
+        assert_eq!(arg_after(&webm, "-c:v"), "libvpx-vp9");
+        assert_eq!(arg_after(&webm, "-pix_fmt"), "yuva420p");
+
+        let prores = args_of(&prores_command("ffmpeg", "/tmp/in.mp4", (1920, 1080), 60.0, &config));
+        assert_eq!(arg_after(&prores, "-profile:v"), "4444");
+        assert_eq!(arg_after(&prores, "-pix_fmt"), "yuva444p10le");
+
+        let apng = args_of(&apng_command("ffmpeg", (1920, 1080), ANIMATION_FPS, &config));
+        assert_eq!(arg_after(&apng, "-pix_fmt"), "rgba");
+        assert_eq!(arg_after(&apng, "-f"), "apng");
+    }
+
+    #[test]
+    fn test_transparent_background_needs_alpha_format() {
+        let mut project = Project::new(
+            "p1".to_string(),
+            "Test".to_string(),
+            "/nonexistent/recording.mp4".to_string(),
+            "/nonexistent/events.json".to_string(),
+            1920,
+            1080,
+            60.0,
+        );
+        project.frame_style.background = Background::Transparent;
+        let events = RecordedEvents::new(1920.0, 1080.0);
+
+        let mp4 = ExportConfig::default();
+        let err = export_project(&project, &events, &mp4, |_| {}).unwrap_err();
+        assert!(err.contains("transparent"), "got {}", err);
+    }
+}
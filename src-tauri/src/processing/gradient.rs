@@ -0,0 +1,292 @@
+use std::f64::consts::TAU;
+
+use image::{Rgba, RgbaImage};
+
+use crate::models::effects::GradientKind;
+use crate::processing::background::parse_hex_color;
+
+/// Colors placed at positions along a gradient
+pub struct ColorRamp {
+    /// (position, rgb) sorted by position
+    stops: Vec<(f64, [f64; 3])>,
+}
+
+impl ColorRamp {
+    /// Build a ramp from hex colors and their positions (0.0 - 1.0).
+    /// Colors are spread evenly when `positions` is empty.
+    pub fn new(colors: &[String], positions: &[f64]) -> Result<Self, String> {
+        if colors.is_empty() {
+            return Err("Gradient needs at least one color".to_string());
+        }
+        if !positions.is_empty() && positions.len() != colors.len() {
+            return Err(format!(
+                "Gradient has {} colors but {} stop positions",
+                colors.len(),
+                positions.len()
+            ));
+        }
+        if positions.iter().any(|p| !(0.0..=1.0).contains(p)) {
+            return Err("Gradient stop positions must be between 0.0 and 1.0".to_string());
+        }
+        if positions.windows(2).any(|w| w[1] < w[0]) {
+            return Err("Gradient stop positions must be in ascending order".to_string());
+        }
+
+        let last = (colors.len() - 1).max(1) as f64;
+        let stops = colors
+            .iter()
+            .enumerate()
+            .map(|(i, color)| {
+                let position = positions.get(i).copied().unwrap_or(i as f64 / last);
+                let rgba = parse_hex_color(color);
+                (position, [rgba[0] as f64, rgba[1] as f64, rgba[2] as f64])
+            })
+            .collect();
+
+        Ok(Self { stops })
+    }
+
+    /// Color at `t`, holding the end colors beyond the first and last stop
+    pub fn sample(&self, t: f64) -> [f64; 3] {
+        let (first_pos, first) = self.stops[0];
+        if t <= first_pos {
+            return first;
+        }
+
+        for pair in self.stops.windows(2) {
+            let (p1, c1) = pair[0];
+            let (p2, c2) = pair[1];
+            if t <= p2 {
+                // Coincident stops make a hard edge
+                let local_t = if p2 > p1 { (t - p1) / (p2 - p1) } else { 1.0 };
+                return [
+                    c1[0] + (c2[0] - c1[0]) * local_t,
+                    c1[1] + (c2[1] - c1[1]) * local_t,
+                    c1[2] + (c2[2] - c1[2]) * local_t,
+                ];
+            }
+        }
+
+        self.stops[self.stops.len() - 1].1
+    }
+}
+
+/// Maps canvas pixels to a position along the gradient
+enum Geometry {
+    Linear { dx: f64, dy: f64, min: f64, range: f64 },
+    Radial { cx: f64, cy: f64, radius: f64 },
+    Conic { cx: f64, cy: f64, start: f64 },
+}
+
+impl Geometry {
+    fn new(kind: &GradientKind, width: f64, height: f64, angle_deg: f64, center: (f64, f64)) -> Self {
+        let angle_rad = angle_deg.to_radians();
+        let (cx, cy) = (center.0 * width, center.1 * height);
+        let corners = [(0.0, 0.0), (width, 0.0), (0.0, height), (width, height)];
+
+        match kind {
+            GradientKind::Linear => {
+                let (dx, dy) = (angle_rad.cos(), angle_rad.sin());
+                // Project corners onto the gradient line to find min/max
+                let projections = corners.map(|(x, y)| x * dx + y * dy);
+                let min = projections.iter().cloned().fold(f64::INFINITY, f64::min);
+                let max = projections.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+                Self::Linear { dx, dy, min, range: (max - min).max(f64::EPSILON) }
+            }
+            GradientKind::Radial => {
+                // Reach the farthest corner so the last color only shows there
+                let radius = corners
+                    .iter()
+                    .map(|(x, y)| (x - cx).hypot(y - cy))
+                    .fold(f64::EPSILON, f64::max);
+                Self::Radial { cx, cy, radius }
+            }
+            GradientKind::Conic => Self::Conic { cx, cy, start: angle_rad },
+        }
+    }
+
+    fn position(&self, x: f64, y: f64) -> f64 {
+        match *self {
+            Self::Linear { dx, dy, min, range } => ((x * dx + y * dy - min) / range).clamp(0.0, 1.0),
+            Self::Radial { cx, cy, radius } => ((x - cx).hypot(y - cy) / radius).clamp(0.0, 1.0),
+            Self::Conic { cx, cy, start } => ((y - cy).atan2(x - cx) - start).rem_euclid(TAU) / TAU,
+        }
+    }
+}
+
+/// Fill the canvas with a gradient.
+///
+/// `angle_deg` is the direction of a linear gradient and the start of a
+/// conic sweep (0 = pointing right, clockwise). `center` is a fraction of
+/// the canvas size. Dithering adds under one level of triangular noise per
+/// channel before quantizing, which breaks up 8-bit banding on large
+/// canvases without visibly changing the colors.
+pub fn draw_gradient(
+    canvas: &mut RgbaImage,
+    ramp: &ColorRamp,
+    kind: &GradientKind,
+    angle_deg: f64,
+    center: (f64, f64),
+    dither: bool,
+) {
+    let geometry = Geometry::new(
+        kind,
+        canvas.width() as f64,
+        canvas.height() as f64,
+        angle_deg,
+        center,
+    );
+
+    for (x, y, pixel) in canvas.enumerate_pixels_mut() {
+        let color = ramp.sample(geometry.position(x as f64 + 0.5, y as f64 + 0.5));
+        let mut out = [0u8, 0, 0, 255];
+        for c in 0..3 {
+            let noise = if dither { triangular_noise(x, y, c as u32) } else { 0.0 };
+            out[c] = (color[c] + noise).round().clamp(0.0, 255.0) as u8;
+        }
+        *pixel = Rgba(out);
+    }
+}
+
+/// Deterministic noise in (-1, 1) with a triangular distribution, so the
+/// same canvas always renders identically
+fn triangular_noise(x: u32, y: u32, channel: u32) -> f64 {
+    let seed = x.wrapping_mul(0x9E37_79B1) ^ y.wrapping_mul(0x85EB_CA77) ^ channel.wrapping_mul(0xC2B2_AE3D);
+    let a = hash(seed);
+    let b = hash(seed ^ 0x27D4_EB2F);
+    (a as f64 + b as f64) / u32::MAX as f64 - 1.0
+}
+
+fn hash(mut v: u32) -> u32 {
+    v ^= v >> 16;
+    v = v.wrapping_mul(0x7FEB_352D);
+    v ^= v >> 15;
+    v = v.wrapping_mul(0x846C_A68B);
+    v ^ (v >> 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors(hex: &[&str]) -> Vec<String> {
+        hex.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn render(ramp: &ColorRamp, kind: GradientKind, angle: f64, dither: bool) -> RgbaImage {
+        let mut canvas = RgbaImage::new(101, 101);
+        draw_gradient(&mut canvas, ramp, &kind, angle, (0.5, 0.5), dither);
+        canvas
+    }
+
+    #[test]
+    fn test_ramp_even_spacing() {
+        let ramp = ColorRamp::new(&colors(&["#000000", "#ffffff", "#000000"]), &[]).unwrap();
+        assert_eq!(ramp.sample(0.0)[0], 0.0);
+        assert_eq!(ramp.sample(0.5)[0], 255.0);
+        assert_eq!(ramp.sample(0.25)[0], 127.5);
+        assert_eq!(ramp.sample(1.0)[0], 0.0);
+    }
+
+    #[test]
+    fn test_ramp_explicit_positions() {
+        let ramp = ColorRamp::new(&colors(&["#000000", "#ffffff"]), &[0.2, 0.4]).unwrap();
+        assert_eq!(ramp.sample(0.1)[0], 0.0);
+        assert!((ramp.sample(0.3)[0] - 127.5).abs() < 1e-9);
+        assert_eq!(ramp.sample(0.9)[0], 255.0);
+    }
+
+    #[test]
+    fn test_ramp_hard_stop() {
+        let ramp = ColorRamp::new(&colors(&["#ff0000", "#0000ff"]), &[0.5, 0.5]).unwrap();
+        assert_eq!(ramp.sample(0.49), [255.0, 0.0, 0.0]);
+        assert_eq!(ramp.sample(0.51), [0.0, 0.0, 255.0]);
+    }
+
+    #[test]
+    fn test_ramp_rejects_bad_stops() {
+        let two = colors(&["#000", "#fff"]);
+        assert!(ColorRamp::new(&two, &[0.0]).is_err());
+        assert!(ColorRamp::new(&two, &[0.6, 0.4]).is_err());
+        assert!(ColorRamp::new(&two, &[0.0, 1.5]).is_err());
+        assert!(ColorRamp::new(&[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_single_color_fills() {
+        let ramp = ColorRamp::new(&colors(&["#336699"]), &[]).unwrap();
+        let canvas = render(&ramp, GradientKind::Radial, 0.0, false);
+        assert!(canvas.pixels().all(|p| *p == Rgba([0x33, 0x66, 0x99, 255])));
+    }
+
+    #[test]
+    fn test_linear_direction() {
+        let ramp = ColorRamp::new(&colors(&["#000000", "#ffffff"]), &[]).unwrap();
+        let canvas = render(&ramp, GradientKind::Linear, 0.0, false);
+        assert!(canvas.get_pixel(0, 50)[0] < 5);
+        assert!(canvas.get_pixel(100, 50)[0] > 250);
+        // Constant along the perpendicular
+        assert_eq!(canvas.get_pixel(40, 0), canvas.get_pixel(40, 100));
+    }
+
+    #[test]
+    fn test_radial_center_and_rings() {
+        let ramp = ColorRamp::new(&colors(&["#ffffff", "#000000"]), &[]).unwrap();
+        let canvas = render(&ramp, GradientKind::Radial, 0.0, false);
+        let center = canvas.get_pixel(50, 50)[0];
+        assert!(center > 250);
+        // Same distance, same color
+        assert_eq!(canvas.get_pixel(20, 50), canvas.get_pixel(50, 20));
+        assert!(canvas.get_pixel(0, 0)[0] < canvas.get_pixel(0, 50)[0]);
+    }
+
+    #[test]
+    fn test_conic_sweep() {
+        let ramp = ColorRamp::new(&colors(&["#000000", "#ffffff"]), &[]).unwrap();
+        let canvas = render(&ramp, GradientKind::Conic, 0.0, false);
+        // Starts pointing right and increases clockwise (down, left, up)
+        let right = canvas.get_pixel(90, 50)[0];
+        let down = canvas.get_pixel(50, 90)[0];
+        let left = canvas.get_pixel(10, 50)[0];
+        let up = canvas.get_pixel(50, 10)[0];
+        assert!(right < 5);
+        assert!(right < down && down < left && left < up);
+        assert!((left as i32 - 128).abs() <= 2);
+    }
+
+    #[test]
+    fn test_conic_start_angle() {
+        let ramp = ColorRamp::new(&colors(&["#000000", "#ffffff"]), &[]).unwrap();
+        let canvas = render(&ramp, GradientKind::Conic, 90.0, false);
+        assert!(canvas.get_pixel(50, 90)[0] < 5);
+    }
+
+    #[test]
+    fn test_dither_preserves_average() {
+        // A very shallow gradient: plain quantization gives wide flat bands
+        let ramp = ColorRamp::new(&colors(&["#404040", "#444444"]), &[]).unwrap();
+        let plain = render(&ramp, GradientKind::Linear, 0.0, false);
+        let dithered = render(&ramp, GradientKind::Linear, 0.0, true);
+
+        let column_mean = |image: &RgbaImage, x: u32| {
+            (0..image.height()).map(|y| image.get_pixel(x, y)[0] as f64).sum::<f64>() / image.height() as f64
+        };
+        for x in [10, 30, 60, 90] {
+            let expected = ramp.sample(x as f64 / 101.0)[0];
+            assert!((column_mean(&dithered, x) - expected).abs() < 0.25);
+        }
+
+        // Dithering breaks up a band into mixed values
+        let column: Vec<u8> = (0..101).map(|y| dithered.get_pixel(12, y)[0]).collect();
+        assert!(column.iter().any(|&v| v != column[0]));
+        assert!((0..101).all(|y| plain.get_pixel(12, y)[0] == plain.get_pixel(12, 0)[0]));
+    }
+
+    #[test]
+    fn test_dither_is_deterministic() {
+        let ramp = ColorRamp::new(&colors(&["#000000", "#ffffff"]), &[]).unwrap();
+        let a = render(&ramp, GradientKind::Radial, 0.0, true);
+        let b = render(&ramp, GradientKind::Radial, 0.0, true);
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,141 @@
+use image::RgbaImage;
+
+use crate::models::effects::ColorGrade;
+use crate::processing::layer::{FrameContext, Layer, Surfaces};
+use crate::processing::lut::Lut3d;
+
+/// Rec. 709 luma weights
+const LUMA: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// Applies a project's color grade to video frames
+pub struct ColorGrader {
+    /// Brightness and contrast for each 8-bit channel value, in 0.0 - 1.0
+    tone: [f32; 256],
+    saturation: f32,
+    lut: Option<(Lut3d, f32)>,
+}
+
+impl ColorGrader {
+    /// Load the LUT if any. Returns `None` when the grade changes nothing.
+    pub fn new(grade: &ColorGrade) -> Result<Option<Self>, String> {
+        let lut = match &grade.lut {
+            Some(config) if config.strength > 0.0 => {
+                Some((Lut3d::load(&config.path)?, config.strength.min(1.0) as f32))
+            }
+            _ => None,
+        };
+        Ok(Self::with_lut(grade, lut))
+    }
+
+    fn with_lut(grade: &ColorGrade, lut: Option<(Lut3d, f32)>) -> Option<Self> {
+        let brightness = grade.brightness.clamp(-1.0, 1.0) as f32;
+        let contrast = grade.contrast.clamp(-1.0, 1.0) as f32;
+        let saturation = grade.saturation.clamp(-1.0, 1.0) as f32;
+        if brightness == 0.0 && contrast == 0.0 && saturation == 0.0 && lut.is_none() {
+            return None;
+        }
+
+        let mut tone = [0f32; 256];
+        for (value, out) in tone.iter_mut().enumerate() {
+            let v = value as f32 / 255.0 + brightness;
+            *out = ((v - 0.5) * (1.0 + contrast) + 0.5).clamp(0.0, 1.0);
+        }
+        Some(Self { tone, saturation, lut })
+    }
+
+    /// Grade the frame in place, leaving alpha untouched
+    pub fn apply(&self, frame: &mut RgbaImage) {
+        for pixel in frame.pixels_mut() {
+            let mut rgb = [self.tone[pixel[0] as usize], self.tone[pixel[1] as usize], self.tone[pixel[2] as usize]];
+
+            if self.saturation != 0.0 {
+                let luma = rgb[0] * LUMA[0] + rgb[1] * LUMA[1] + rgb[2] * LUMA[2];
+                for c in &mut rgb {
+                    *c = (luma + (*c - luma) * (1.0 + self.saturation)).clamp(0.0, 1.0);
+                }
+            }
+
+            if let Some((lut, strength)) = &self.lut {
+                let graded = lut.sample(rgb);
+                for c in 0..3 {
+                    rgb[c] += (graded[c] - rgb[c]) * strength;
+                }
+            }
+
+            for c in 0..3 {
+                pixel[c] = (rgb[c].clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        }
+    }
+}
+
+impl Layer for ColorGrader {
+    fn render(&self, surfaces: &mut Surfaces, _ctx: &FrameContext) {
+        self.apply(&mut surfaces.video);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+    use crate::models::effects::LutConfig;
+
+    fn grade(brightness: f64, contrast: f64, saturation: f64) -> ColorGrade {
+        ColorGrade { brightness, contrast, saturation, lut: None }
+    }
+
+    fn graded(grader: &ColorGrader, color: Rgba<u8>) -> Rgba<u8> {
+        let mut frame = RgbaImage::from_pixel(1, 1, color);
+        grader.apply(&mut frame);
+        *frame.get_pixel(0, 0)
+    }
+
+    #[test]
+    fn test_neutral_grade_is_skipped() {
+        assert!(ColorGrader::new(&ColorGrade::default()).unwrap().is_none());
+        let zero_strength = ColorGrade {
+            lut: Some(LutConfig { path: "/nonexistent.cube".to_string(), strength: 0.0 }),
+            ..ColorGrade::default()
+        };
+        assert!(ColorGrader::new(&zero_strength).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_brightness_and_contrast() {
+        let brighter = ColorGrader::with_lut(&grade(0.2, 0.0, 0.0), None).unwrap();
+        assert_eq!(graded(&brighter, Rgba([100, 100, 100, 255])), Rgba([151, 151, 151, 255]));
+
+        let flat = ColorGrader::with_lut(&grade(0.0, -1.0, 0.0), None).unwrap();
+        assert_eq!(graded(&flat, Rgba([0, 255, 30, 200])), Rgba([128, 128, 128, 200]));
+    }
+
+    #[test]
+    fn test_desaturate() {
+        let gray = ColorGrader::with_lut(&grade(0.0, 0.0, -1.0), None).unwrap();
+        let pixel = graded(&gray, Rgba([255, 0, 0, 255]));
+        assert_eq!(pixel[0], pixel[1]);
+        assert_eq!(pixel[1], pixel[2]);
+        assert_eq!(pixel[0], 54);
+    }
+
+    #[test]
+    fn test_lut_strength() {
+        let invert = Lut3d::parse("LUT_3D_SIZE 2\n1 1 1\n0 1 1\n1 0 1\n0 0 1\n1 1 0\n0 1 0\n1 0 0\n0 0 0\n").unwrap();
+        let full = ColorGrader::with_lut(&ColorGrade::default(), Some((invert.clone(), 1.0))).unwrap();
+        assert_eq!(graded(&full, Rgba([255, 0, 51, 255])), Rgba([0, 255, 204, 255]));
+
+        let half = ColorGrader::with_lut(&ColorGrade::default(), Some((invert, 0.5))).unwrap();
+        assert_eq!(graded(&half, Rgba([255, 0, 0, 255])), Rgba([128, 128, 128, 255]));
+    }
+
+    #[test]
+    fn test_missing_lut_errors() {
+        let missing = ColorGrade {
+            lut: Some(LutConfig { path: "/nonexistent.cube".to_string(), strength: 1.0 }),
+            ..ColorGrade::default()
+        };
+        assert!(ColorGrader::new(&missing).is_err());
+    }
+}
@@ -0,0 +1,354 @@
+use image::{Rgba, RgbaImage};
+
+use crate::models::effects::KeystrokeConfig;
+use crate::models::events::KeyEvent;
+use crate::processing::layer::{FrameContext, Layer, Surfaces};
+use crate::processing::shape::Shape;
+use crate::processing::text::Font;
+
+/// Fade applied to a badge that times out, rather than being replaced
+const FADE_OUT_MS: f64 = 150.0;
+/// Typed text longer than this only shows its tail
+const MAX_TYPED_CHARS: usize = 24;
+const BADGE_COLOR: Rgba<u8> = Rgba([24, 24, 27, 210]);
+const TEXT_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+/// Every symbol `combo_label` can put in a badge
+const BADGE_SYMBOLS: &str = "⌃⌥⇧⌘↩⌤⇥⌫⌦←→↑↓↖↘⇞⇟";
+
+/// A label shown from `start_ms` until `end_ms`
+#[derive(Debug, Clone, PartialEq)]
+struct Badge {
+    start_ms: u64,
+    end_ms: u64,
+    label: String,
+    /// False when a later key press replaces the badge
+    fades_out: bool,
+}
+
+/// Renders recent key presses as a badge centered near the bottom of the canvas
+pub struct KeystrokeLayer {
+    badges: Vec<Badge>,
+    font: Font,
+    font_size: f64,
+}
+
+impl KeystrokeLayer {
+    /// Returns `None` when disabled or when nothing was recorded
+    pub fn new(events: &[KeyEvent], config: &KeystrokeConfig) -> Result<Option<Self>, String> {
+        if !config.enabled || events.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            badges: build_badges(events, config.display_ms),
+            font: badge_font(),
+            font_size: config.font_size,
+        }))
+    }
+
+    pub fn draw(&self, canvas: &mut RgbaImage, time_ms: u64) {
+        let Some(badge) = badge_at(&self.badges, time_ms) else {
+            return;
+        };
+        let opacity = if badge.fades_out {
+            ((badge.end_ms - time_ms) as f64 / FADE_OUT_MS).min(1.0)
+        } else {
+            1.0
+        };
+
+        let size = self.font_size;
+        let (pad_x, pad_y) = (size * 0.6, size * 0.35);
+        let text_width = self.font.text_width(&badge.label, size);
+        let width = text_width + pad_x * 2.0;
+        let height = self.font.line_height(size) + pad_y * 2.0;
+        let x = (canvas.width() as f64 - width) / 2.0;
+        let y = canvas.height() as f64 - height - size * 1.5;
+
+        Shape::rounded(x, y, width, height, size * 0.4).fill(canvas, with_opacity(BADGE_COLOR, opacity));
+        self.font.draw_text(canvas, &badge.label, x + pad_x, y + pad_y, size, with_opacity(TEXT_COLOR, opacity));
+    }
+}
+
+impl Layer for KeystrokeLayer {
+    fn render(&self, surfaces: &mut Surfaces, ctx: &FrameContext) {
+        self.draw(&mut surfaces.canvas, ctx.time_ms);
+    }
+}
+
+/// Group key presses into badges. Shortcuts get a badge each; plain typing
+/// in quick succession accumulates into one growing badge.
+fn build_badges(events: &[KeyEvent], display_ms: u64) -> Vec<Badge> {
+    let mut badges: Vec<Badge> = Vec::new();
+    let mut typed = String::new();
+
+    for event in events {
+        let continues_typing = badges
+            .last()
+            .is_some_and(|last| !typed.is_empty() && event.timestamp_ms < last.end_ms);
+
+        let label = match typed_char(event) {
+            Some(c) => {
+                if !continues_typing {
+                    typed.clear();
+                }
+                typed.push(c);
+                tail(&typed, MAX_TYPED_CHARS)
+            }
+            None if continues_typing && event.key == "Delete" && !event.is_shortcut() && !event.modifiers.option => {
+                typed.pop();
+                if typed.is_empty() {
+                    combo_label(event)
+                } else {
+                    tail(&typed, MAX_TYPED_CHARS)
+                }
+            }
+            None => {
+                typed.clear();
+                combo_label(event)
+            }
+        };
+
+        if let Some(last) = badges.last_mut() {
+            if event.timestamp_ms < last.end_ms {
+                last.end_ms = event.timestamp_ms;
+                last.fades_out = false;
+            }
+        }
+        badges.push(Badge {
+            start_ms: event.timestamp_ms,
+            end_ms: event.timestamp_ms + display_ms,
+            label,
+            fades_out: true,
+        });
+    }
+
+    badges.retain(|badge| badge.end_ms > badge.start_ms);
+    badges
+}
+
+fn badge_at(badges: &[Badge], time_ms: u64) -> Option<&Badge> {
+    let index = badges.partition_point(|badge| badge.start_ms <= time_ms);
+    let badge = badges.get(index.checked_sub(1)?)?;
+    (time_ms < badge.end_ms).then_some(badge)
+}
+
+/// The character a plain key press types, if any. Option types
+/// layout-specific characters, so those presses show as combos instead.
+fn typed_char(event: &KeyEvent) -> Option<char> {
+    if event.is_shortcut() || event.modifiers.option {
+        return None;
+    }
+    if event.key == "Space" {
+        return Some(' ');
+    }
+    let mut chars = event.key.chars();
+    let c = chars.next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+    Some(if event.modifiers.shift { shifted(c) } else { c.to_ascii_lowercase() })
+}
+
+/// What shift turns a key into on a US layout, which key names follow
+fn shifted(c: char) -> char {
+    match c {
+        '1' => '!',
+        '2' => '@',
+        '3' => '#',
+        '4' => '$',
+        '5' => '%',
+        '6' => '^',
+        '7' => '&',
+        '8' => '*',
+        '9' => '(',
+        '0' => ')',
+        '-' => '_',
+        '=' => '+',
+        '[' => '{',
+        ']' => '}',
+        '\\' => '|',
+        ';' => ':',
+        '\'' => '"',
+        ',' => '<',
+        '.' => '>',
+        '/' => '?',
+        '`' => '~',
+        other => other.to_ascii_uppercase(),
+    }
+}
+
+/// macOS-style combo such as "⇧⌘P", with modifiers in menu order
+fn combo_label(event: &KeyEvent) -> String {
+    let m = &event.modifiers;
+    let mut label = String::new();
+    for (held, symbol) in [(m.control, '⌃'), (m.option, '⌥'), (m.shift, '⇧'), (m.command, '⌘')] {
+        if held {
+            label.push(symbol);
+        }
+    }
+    label.push_str(key_symbol(&event.key));
+    label
+}
+
+fn key_symbol(key: &str) -> &str {
+    match key {
+        "Return" => "↩",
+        "Enter" => "⌤",
+        "Tab" => "⇥",
+        "Delete" => "⌫",
+        "ForwardDelete" => "⌦",
+        // The bundled font has no ⎋
+        "Escape" => "esc",
+        "Left" => "←",
+        "Right" => "→",
+        "Up" => "↑",
+        "Down" => "↓",
+        "Home" => "↖",
+        "End" => "↘",
+        "PageUp" => "⇞",
+        "PageDown" => "⇟",
+        other => other,
+    }
+}
+
+/// The system font when it can draw every badge symbol, otherwise the
+/// bundled one, so shortcuts never show as empty boxes
+fn badge_font() -> Font {
+    let font = Font::system();
+    if font.has_glyphs(BADGE_SYMBOLS) {
+        font
+    } else {
+        Font::bundled()
+    }
+}
+
+fn tail(text: &str, max_chars: usize) -> String {
+    let count = text.chars().count();
+    text.chars().skip(count.saturating_sub(max_chars)).collect()
+}
+
+fn with_opacity(color: Rgba<u8>, opacity: f64) -> Rgba<u8> {
+    Rgba([color[0], color[1], color[2], (color[3] as f64 * opacity).round() as u8])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::events::Modifiers;
+
+    const COMMAND: Modifiers = Modifiers { command: true, shift: false, option: false, control: false };
+
+    fn key(timestamp_ms: u64, key: &str, modifiers: Modifiers) -> KeyEvent {
+        KeyEvent { timestamp_ms, key: key.to_string(), modifiers }
+    }
+
+    fn labels(badges: &[Badge]) -> Vec<&str> {
+        badges.iter().map(|badge| badge.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_combo_label() {
+        let all = Modifiers { command: true, shift: true, option: true, control: true };
+        assert_eq!(combo_label(&key(0, "P", Modifiers { shift: true, ..COMMAND })), "⇧⌘P");
+        assert_eq!(combo_label(&key(0, "Left", all)), "⌃⌥⇧⌘←");
+        assert_eq!(combo_label(&key(0, "Return", Modifiers::default())), "↩");
+    }
+
+    #[test]
+    fn test_shortcuts_replace_each_other() {
+        let badges = build_badges(&[key(0, "C", COMMAND), key(500, "V", COMMAND)], 1500);
+        assert_eq!(labels(&badges), ["⌘C", "⌘V"]);
+        assert_eq!((badges[0].end_ms, badges[0].fades_out), (500, false));
+        assert_eq!((badges[1].end_ms, badges[1].fades_out), (2000, true));
+    }
+
+    #[test]
+    fn test_typing_accumulates() {
+        let shift = Modifiers { shift: true, ..Modifiers::default() };
+        let events = [
+            key(0, "H", shift),
+            key(100, "I", Modifiers::default()),
+            key(200, "Space", Modifiers::default()),
+            key(300, "X", Modifiers::default()),
+            key(400, "Delete", Modifiers::default()),
+            // A pause longer than the display time starts a new badge
+            key(5000, "A", Modifiers::default()),
+        ];
+        let badges = build_badges(&events, 1500);
+        assert_eq!(labels(&badges), ["H", "Hi", "Hi ", "Hi x", "Hi ", "a"]);
+    }
+
+    #[test]
+    fn test_shift_types_symbols() {
+        let shift = Modifiers { shift: true, ..Modifiers::default() };
+        let events = [key(0, "1", shift), key(100, "/", shift), key(200, "1", Modifiers::default())];
+        assert_eq!(labels(&build_badges(&events, 1500)), ["!", "!?", "!?1"]);
+    }
+
+    #[test]
+    fn test_option_shows_as_combo() {
+        let option = Modifiers { option: true, ..Modifiers::default() };
+        let events = [key(0, "A", Modifiers::default()), key(100, "E", option)];
+        assert_eq!(labels(&build_badges(&events, 1500)), ["a", "⌥E"]);
+    }
+
+    #[test]
+    fn test_typing_is_cut_by_shortcut() {
+        let events = [
+            key(0, "A", Modifiers::default()),
+            key(100, "S", COMMAND),
+            key(200, "B", Modifiers::default()),
+        ];
+        assert_eq!(labels(&build_badges(&events, 1500)), ["a", "⌘S", "b"]);
+    }
+
+    #[test]
+    fn test_badge_at() {
+        let badges = build_badges(&[key(1000, "C", COMMAND), key(1200, "V", COMMAND)], 1500);
+        assert!(badge_at(&badges, 999).is_none());
+        assert_eq!(badge_at(&badges, 1000).unwrap().label, "⌘C");
+        assert_eq!(badge_at(&badges, 1200).unwrap().label, "⌘V");
+        assert_eq!(badge_at(&badges, 2699).unwrap().label, "⌘V");
+        assert!(badge_at(&badges, 2700).is_none());
+    }
+
+    #[test]
+    fn test_badge_font_draws_every_symbol() {
+        assert!(Font::bundled().has_glyphs(BADGE_SYMBOLS));
+        assert!(badge_font().has_glyphs(BADGE_SYMBOLS));
+
+        let all = Modifiers { command: true, shift: true, option: true, control: true };
+        for name in ["Return", "Enter", "Tab", "Delete", "ForwardDelete", "Escape", "Left", "Right", "Up", "Down", "Home", "End", "PageUp", "PageDown"] {
+            let label = combo_label(&key(0, name, all));
+            assert!(label.chars().all(|c| c.is_ascii() || BADGE_SYMBOLS.contains(c)), "{} is missing from BADGE_SYMBOLS", label);
+        }
+    }
+
+    #[test]
+    fn test_tail() {
+        assert_eq!(tail("hello", 3), "llo");
+        assert_eq!(tail("hi", 3), "hi");
+    }
+
+    #[test]
+    fn test_disabled_or_empty_has_no_layer() {
+        let config = KeystrokeConfig { enabled: false, ..KeystrokeConfig::default() };
+        assert!(KeystrokeLayer::new(&[key(0, "C", COMMAND)], &config).unwrap().is_none());
+        assert!(KeystrokeLayer::new(&[], &KeystrokeConfig::default()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_draw_badge() {
+        let layer = KeystrokeLayer::new(&[key(0, "P", COMMAND)], &KeystrokeConfig::default())
+            .unwrap()
+            .unwrap();
+        let background = Rgba([255, 255, 255, 255]);
+        let mut canvas = RgbaImage::from_pixel(400, 300, background);
+        layer.draw(&mut canvas, 100);
+        assert_ne!(*canvas.get_pixel(200, 220), background);
+        assert_eq!(*canvas.get_pixel(200, 20), background);
+
+        let mut later = RgbaImage::from_pixel(400, 300, background);
+        layer.draw(&mut later, 1600);
+        assert!(later.pixels().all(|p| *p == background));
+    }
+}
@@ -0,0 +1,61 @@
+use std::borrow::Cow;
+
+use image::RgbaImage;
+
+use crate::processing::zoom::FrameViewport;
+
+/// Sizes fixed for a whole export
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExportGeometry {
+    pub source_width: u32,
+    pub source_height: u32,
+    pub canvas_width: u32,
+    pub canvas_height: u32,
+    /// Time between output frames
+    pub frame_interval_ms: f64,
+}
+
+/// Per-frame inputs shared by every layer
+pub struct FrameContext<'a> {
+    pub time_ms: u64,
+    /// The part of the source shown at `time_ms`, in source pixels
+    pub viewport: FrameViewport,
+    /// The picture-in-picture video frame, if the project has one
+    pub overlay_frame: Option<&'a RgbaImage>,
+}
+
+/// The images a frame is built up in, from the decoded recording to the
+/// output canvas. Layers earlier in the stack fill in the later images.
+pub struct Surfaces<'a> {
+    /// The decoded recording; layers that change it take a copy first
+    pub source: Cow<'a, RgbaImage>,
+    /// The recording after zoom, at source size. Empty until the zoom layer runs.
+    pub video: RgbaImage,
+    /// The output canvas. Empty until the frame layer runs.
+    pub canvas: RgbaImage,
+}
+
+impl<'a> Surfaces<'a> {
+    pub fn new(source: &'a RgbaImage) -> Self {
+        Self {
+            source: Cow::Borrowed(source),
+            video: RgbaImage::new(0, 0),
+            canvas: RgbaImage::new(0, 0),
+        }
+    }
+}
+
+/// One step of the compositor's layer stack.
+///
+/// Layers are built from the project, prepared once per export and then
+/// rendered for every frame, possibly from several threads at once.
+pub trait Layer: Send + Sync {
+    /// Called once before the first frame, for work that needs the final
+    /// export geometry
+    fn prepare(&mut self, _geometry: &ExportGeometry) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Draw this layer's contribution to the frame at `ctx.time_ms`
+    fn render(&self, surfaces: &mut Surfaces, ctx: &FrameContext);
+}
@@ -0,0 +1,144 @@
+/// Largest grid accepted, matching what grading tools export
+const MAX_LUT_SIZE: usize = 256;
+
+/// A 3D color lookup table sampled with trilinear interpolation
+#[derive(Debug, Clone)]
+pub struct Lut3d {
+    size: usize,
+    /// Output colors with red varying fastest, then green, then blue
+    table: Vec<[f32; 3]>,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+}
+
+impl Lut3d {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read LUT {}: {}", path, e))?;
+        Self::parse(&text).map_err(|e| format!("Invalid LUT {}: {}", path, e))
+    }
+
+    /// Parse the `.cube` text format
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut size = None;
+        let mut table = Vec::new();
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let first = parts.next().unwrap_or_default();
+            match first {
+                "LUT_3D_SIZE" => {
+                    let value = parts.next().and_then(|v| v.parse::<usize>().ok());
+                    match value {
+                        Some(n) if (2..=MAX_LUT_SIZE).contains(&n) => size = Some(n),
+                        _ => return Err(format!("line {}: bad LUT_3D_SIZE", index + 1)),
+                    }
+                }
+                "LUT_1D_SIZE" => return Err("1D LUTs are not supported".to_string()),
+                "DOMAIN_MIN" => domain_min = parse_triplet(parts, index)?,
+                "DOMAIN_MAX" => domain_max = parse_triplet(parts, index)?,
+                // TITLE and vendor-specific keywords
+                keyword if keyword.starts_with(|c: char| c.is_ascii_alphabetic()) => {}
+                _ => table.push(parse_triplet(line.split_whitespace(), index)?),
+            }
+        }
+
+        let size = size.ok_or("missing LUT_3D_SIZE")?;
+        if table.len() != size * size * size {
+            return Err(format!("expected {} entries, found {}", size * size * size, table.len()));
+        }
+        if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+            return Err("DOMAIN_MAX must be above DOMAIN_MIN".to_string());
+        }
+        Ok(Self { size, table, domain_min, domain_max })
+    }
+
+    /// Look up a color with channels in 0.0 - 1.0
+    pub fn sample(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let max_index = (self.size - 1) as f32;
+        let mut base = [0usize; 3];
+        let mut frac = [0f32; 3];
+        for c in 0..3 {
+            let range = self.domain_max[c] - self.domain_min[c];
+            let position = ((rgb[c] - self.domain_min[c]) / range).clamp(0.0, 1.0) * max_index;
+            base[c] = (position as usize).min(self.size - 2);
+            frac[c] = position - base[c] as f32;
+        }
+
+        let mut out = [0f32; 3];
+        for corner in 0..8 {
+            let (dr, dg, db) = (corner & 1, (corner >> 1) & 1, (corner >> 2) & 1);
+            let weight = (if dr == 1 { frac[0] } else { 1.0 - frac[0] })
+                * (if dg == 1 { frac[1] } else { 1.0 - frac[1] })
+                * (if db == 1 { frac[2] } else { 1.0 - frac[2] });
+            if weight == 0.0 {
+                continue;
+            }
+            let entry = self.table[self.index(base[0] + dr, base[1] + dg, base[2] + db)];
+            for c in 0..3 {
+                out[c] += entry[c] * weight;
+            }
+        }
+        out
+    }
+
+    fn index(&self, r: usize, g: usize, b: usize) -> usize {
+        r + g * self.size + b * self.size * self.size
+    }
+}
+
+fn parse_triplet<'a>(mut parts: impl Iterator<Item = &'a str>, index: usize) -> Result<[f32; 3], String> {
+    let mut values = [0f32; 3];
+    for value in &mut values {
+        *value = parts
+            .next()
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| format!("line {}: expected three numbers", index + 1))?;
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x2x2 LUT mapping each color to its inverse
+    const INVERT: &str = "TITLE \"Invert\"\n# comment\nLUT_3D_SIZE 2\n\
+        1 1 1\n0 1 1\n1 0 1\n0 0 1\n1 1 0\n0 1 0\n1 0 0\n0 0 0\n";
+
+    fn assert_close(actual: [f32; 3], expected: [f32; 3]) {
+        for c in 0..3 {
+            assert!((actual[c] - expected[c]).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_parse_and_sample() {
+        let lut = Lut3d::parse(INVERT).unwrap();
+        assert_close(lut.sample([0.0, 0.0, 0.0]), [1.0, 1.0, 1.0]);
+        assert_close(lut.sample([1.0, 0.0, 0.0]), [0.0, 1.0, 1.0]);
+        assert_close(lut.sample([0.25, 0.5, 1.0]), [0.75, 0.5, 0.0]);
+        // Out of range input clamps to the domain
+        assert_close(lut.sample([2.0, -1.0, 0.5]), [0.0, 1.0, 0.5]);
+    }
+
+    #[test]
+    fn test_domain() {
+        let text = INVERT.replace("LUT_3D_SIZE 2", "LUT_3D_SIZE 2\nDOMAIN_MIN 0 0 0\nDOMAIN_MAX 2 2 2");
+        let lut = Lut3d::parse(&text).unwrap();
+        assert_close(lut.sample([1.0, 1.0, 1.0]), [0.5, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Lut3d::parse("1 1 1\n").is_err());
+        assert!(Lut3d::parse("LUT_3D_SIZE 2\n0 0 0\n").is_err());
+        assert!(Lut3d::parse("LUT_1D_SIZE 2\n0 0 0\n1 1 1\n").is_err());
+        assert!(Lut3d::parse(&INVERT.replace("0 0 0\n", "0 0\n")).is_err());
+    }
+}
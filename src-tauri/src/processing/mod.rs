@@ -1,5 +1,23 @@
+pub mod annotations;
 pub mod background;
+pub mod blend;
+pub mod chrome;
 pub mod compositor;
 pub mod cursor;
+pub mod decoder;
 pub mod encoder;
+pub mod gradient;
+pub mod grading;
+pub mod keystrokes;
+pub mod layer;
+pub mod lut;
+pub mod overlay;
+pub mod pipeline;
+pub mod preview;
+pub mod redactions;
+pub mod resample;
+pub mod shape;
+pub mod text;
+pub mod thumbnails;
+pub mod watermark;
 pub mod zoom;
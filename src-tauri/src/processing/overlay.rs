@@ -0,0 +1,311 @@
+use image::{Rgba, RgbaImage};
+
+use crate::models::effects::{EasingType, Shadow};
+use crate::models::overlay::{OverlayPosition, OverlayRangeMode, OverlayTrack};
+use crate::processing::background::{rounded_rect_sdf, Corners, FrameRect, ShadowMask};
+use crate::processing::blend::alpha_blend;
+use crate::processing::decoder::FrameDecoder;
+use crate::processing::layer::{FrameContext, Layer, Surfaces};
+use crate::processing::resample;
+use crate::processing::zoom::{self, FrameViewport};
+
+/// How long the overlay takes to hide, show or change size
+const TRANSITION_MS: f64 = 300.0;
+
+/// Reads overlay frames in step with the recording's timeline
+pub struct OverlayReader {
+    decoder: FrameDecoder,
+    /// Recording time of the overlay's first decoded frame
+    start_ms: i64,
+    current: Option<(u64, RgbaImage)>,
+    next: Option<(u64, RgbaImage)>,
+    exhausted: bool,
+}
+
+impl OverlayReader {
+    /// Decode `track` at `fps` from recording time `from_ms`, no wider than
+    /// the overlay is ever drawn
+    pub fn open(track: &OverlayTrack, fps: f64, frame_width: u32, from_ms: u64) -> Result<Self, String> {
+        let max_width = (track.size.max(track.enlarged_size).clamp(0.0, 1.0) * frame_width as f64).ceil() as u32;
+        // A negative offset means the overlay started before the recording
+        let skip_ms = (from_ms as i64 - track.offset_ms).max(0);
+        let decoder = FrameDecoder::open_scaled(&track.path, fps, skip_ms as u64, max_width.max(1))?;
+        Ok(Self {
+            decoder,
+            start_ms: track.offset_ms + skip_ms,
+            current: None,
+            next: None,
+            exhausted: false,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.decoder.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.decoder.height()
+    }
+
+    /// The overlay frame showing at recording time `time_ms`, or `None`
+    /// before the overlay starts or after it ends. Times must not go
+    /// backwards between calls.
+    pub fn frame_at(&mut self, time_ms: u64) -> Result<Option<RgbaImage>, String> {
+        let local_ms = time_ms as i64 - self.start_ms;
+        if local_ms < 0 {
+            return Ok(None);
+        }
+        let local_ms = local_ms as u64;
+
+        loop {
+            if self.next.is_none() && !self.exhausted {
+                self.next = self.decoder.next_frame()?;
+                self.exhausted = self.next.is_none();
+            }
+            match &self.next {
+                Some((frame_ms, _)) if *frame_ms <= local_ms => self.current = self.next.take(),
+                Some(_) => break,
+                None => return Ok(None),
+            }
+        }
+        Ok(self.current.as_ref().map(|(_, frame)| frame.clone()))
+    }
+}
+
+/// Draws overlay frames picture-in-picture onto the zoomed video frame
+pub struct OverlayLayer {
+    track: OverlayTrack,
+    /// Aspect ratio (width / height) of the overlay video
+    aspect: f64,
+    frame_width: u32,
+    frame_height: u32,
+    /// Shadows at the resting and enlarged sizes, which most frames use
+    cached_shadows: Vec<(FrameRect, ShadowMask)>,
+}
+
+impl OverlayLayer {
+    pub fn new(track: &OverlayTrack, overlay_width: u32, overlay_height: u32, frame_width: u32, frame_height: u32) -> Self {
+        let mut layer = Self {
+            track: track.clone(),
+            aspect: overlay_width.max(1) as f64 / overlay_height.max(1) as f64,
+            frame_width,
+            frame_height,
+            cached_shadows: Vec::new(),
+        };
+        layer.cached_shadows = [track.size, track.enlarged_size]
+            .iter()
+            .map(|&size| {
+                let rect = layer.rect_for(size);
+                (rect, ShadowMask::new(&rect, layer.corners(&rect), &track.shadow))
+            })
+            .collect();
+        layer
+    }
+
+    /// Overlay width (fraction of the frame width) and opacity at `time_ms`
+    fn state_at(&self, time_ms: u64) -> (f64, f64) {
+        let mut hidden = 0.0f64;
+        let mut enlarged = 0.0f64;
+        for range in &self.track.ranges {
+            let progress = range_progress(time_ms as f64, range.start_ms as f64, range.end_ms as f64);
+            match range.mode {
+                OverlayRangeMode::Hidden => hidden = hidden.max(progress),
+                OverlayRangeMode::Enlarged => enlarged = enlarged.max(progress),
+            }
+        }
+        let size = self.track.size + (self.track.enlarged_size - self.track.size) * enlarged;
+        (size, 1.0 - hidden)
+    }
+
+    /// Frame rectangle of the overlay at `size`, pinned to its corner
+    fn rect_for(&self, size: f64) -> FrameRect {
+        let margin = self.track.margin;
+        let max_width = self.frame_width.saturating_sub(margin * 2).max(1);
+        let max_height = self.frame_height.saturating_sub(margin * 2).max(1);
+
+        let mut width = (size.max(0.0) * self.frame_width as f64).round().max(1.0);
+        let mut height = if self.track.circle { width } else { width / self.aspect };
+        let fit = (max_width as f64 / width).min(max_height as f64 / height).min(1.0);
+        width = (width * fit).round().max(1.0);
+        height = (height * fit).round().max(1.0);
+        let (width, height) = (width as u32, height as u32);
+
+        let (left, top) = (margin, margin);
+        let right = self.frame_width.saturating_sub(margin + width);
+        let bottom = self.frame_height.saturating_sub(margin + height);
+        let (x, y) = match self.track.position {
+            OverlayPosition::TopLeft => (left, top),
+            OverlayPosition::TopRight => (right, top),
+            OverlayPosition::BottomLeft => (left, bottom),
+            OverlayPosition::BottomRight => (right, bottom),
+        };
+        FrameRect { x, y, width, height }
+    }
+
+    fn corners(&self, rect: &FrameRect) -> Corners {
+        let radius = if self.track.circle {
+            rect.width.min(rect.height) as f64 / 2.0
+        } else {
+            self.track.corner_radius as f64
+        };
+        Corners { radius, exponent: 2.0 }
+    }
+
+    /// Draw `overlay_frame` onto the zoomed frame as it appears at `time_ms`
+    pub fn draw(&self, frame: &mut RgbaImage, overlay_frame: &RgbaImage, time_ms: u64) {
+        let (size, opacity) = self.state_at(time_ms);
+        if opacity <= 0.0 {
+            return;
+        }
+        let rect = self.rect_for(size);
+        let corners = self.corners(&rect);
+
+        let cached = self.cached_shadows.iter().find(|(cached_rect, _)| *cached_rect == rect);
+        match cached {
+            Some((_, shadow)) if opacity >= 1.0 => shadow.draw(frame),
+            _ => {
+                let shadow = Shadow { opacity: self.track.shadow.opacity * opacity, ..self.track.shadow.clone() };
+                ShadowMask::new(&rect, corners, &shadow).draw(frame);
+            }
+        }
+
+        let viewport = cover_viewport(overlay_frame.width(), overlay_frame.height(), rect.width, rect.height);
+        let scaled = resample::sample_viewport(overlay_frame, &viewport, rect.width, rect.height);
+        let (w, h) = (rect.width as f64, rect.height as f64);
+        for (x, y, pixel) in scaled.enumerate_pixels() {
+            let (px, py) = (rect.x + x, rect.y + y);
+            if px >= frame.width() || py >= frame.height() {
+                continue;
+            }
+            let coverage = (0.5 - rounded_rect_sdf(x as f64 + 0.5, y as f64 + 0.5, w, h, corners)).clamp(0.0, 1.0);
+            let alpha = (coverage * opacity * pixel[3] as f64).round() as u8;
+            if alpha == 0 {
+                continue;
+            }
+            let fg = Rgba([pixel[0], pixel[1], pixel[2], alpha]);
+            let blended = alpha_blend(frame.get_pixel(px, py), &fg);
+            frame.put_pixel(px, py, blended);
+        }
+    }
+}
+
+impl Layer for OverlayLayer {
+    fn render(&self, surfaces: &mut Surfaces, ctx: &FrameContext) {
+        if let Some(overlay_frame) = ctx.overlay_frame {
+            self.draw(&mut surfaces.video, overlay_frame, ctx.time_ms);
+        }
+    }
+}
+
+/// 0.0 outside the range, easing to 1.0 over `TRANSITION_MS` at each end
+fn range_progress(time_ms: f64, start_ms: f64, end_ms: f64) -> f64 {
+    if time_ms < start_ms || time_ms >= end_ms {
+        return 0.0;
+    }
+    let ramp = ((time_ms - start_ms) / TRANSITION_MS).min((end_ms - time_ms) / TRANSITION_MS);
+    zoom::apply_easing(ramp.min(1.0), &EasingType::EaseInOut)
+}
+
+/// The centered region of a `source_width` x `source_height` image that
+/// fills a `width` x `height` box without distortion
+fn cover_viewport(source_width: u32, source_height: u32, width: u32, height: u32) -> FrameViewport {
+    let (sw, sh) = (source_width as f64, source_height as f64);
+    let scale = (width as f64 / sw).max(height as f64 / sh);
+    let (crop_w, crop_h) = (width as f64 / scale, height as f64 / scale);
+    FrameViewport {
+        x: (sw - crop_w) / 2.0,
+        y: (sh - crop_h) / 2.0,
+        width: crop_w,
+        height: crop_h,
+        zoom: 1.0,
+        center_x: sw / 2.0,
+        center_y: sh / 2.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::overlay::OverlayRange;
+
+    const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+    fn track() -> OverlayTrack {
+        let mut track: OverlayTrack = serde_json::from_str(r#"{ "path": "/tmp/camera.mp4" }"#).unwrap();
+        track.shadow.opacity = 0.0;
+        track
+    }
+
+    #[test]
+    fn test_rect_pinned_to_corner() {
+        let mut track = track();
+        track.size = 0.25;
+        let layer = OverlayLayer::new(&track, 640, 480, 1000, 600);
+        assert_eq!(layer.rect_for(0.25), FrameRect { x: 718, y: 380, width: 250, height: 188 });
+
+        track.position = OverlayPosition::TopLeft;
+        track.circle = true;
+        let layer = OverlayLayer::new(&track, 640, 480, 1000, 600);
+        assert_eq!(layer.rect_for(0.25), FrameRect { x: 32, y: 32, width: 250, height: 250 });
+    }
+
+    #[test]
+    fn test_rect_fits_inside_frame() {
+        let layer = OverlayLayer::new(&track(), 1000, 1000, 400, 300);
+        let rect = layer.rect_for(1.0);
+        assert!(rect.height <= 300 - 64);
+        assert!(rect.y + rect.height <= 300);
+    }
+
+    #[test]
+    fn test_ranges_hide_and_enlarge() {
+        let mut track = track();
+        track.ranges = vec![
+            OverlayRange { start_ms: 1000, end_ms: 2000, mode: OverlayRangeMode::Hidden },
+            OverlayRange { start_ms: 3000, end_ms: 5000, mode: OverlayRangeMode::Enlarged },
+        ];
+        let layer = OverlayLayer::new(&track, 640, 480, 1000, 600);
+
+        assert_eq!(layer.state_at(500), (track.size, 1.0));
+        assert_eq!(layer.state_at(1500).1, 0.0);
+        let (_, fading) = layer.state_at(1150);
+        assert!(fading > 0.0 && fading < 1.0);
+        let (enlarged, opacity) = layer.state_at(4000);
+        assert!((enlarged - track.enlarged_size).abs() < 1e-9);
+        assert_eq!(opacity, 1.0);
+        assert_eq!(layer.state_at(5000).0, track.size);
+    }
+
+    #[test]
+    fn test_draw_masks_corners() {
+        let mut track = track();
+        track.circle = true;
+        track.size = 0.5;
+        track.margin = 0;
+        let layer = OverlayLayer::new(&track, 50, 50, 100, 100);
+        let mut frame = RgbaImage::from_pixel(100, 100, BLACK);
+        layer.draw(&mut frame, &RgbaImage::from_pixel(50, 50, RED), 0);
+
+        // Bottom-right quarter holds the circle; its bounding box corner does not
+        assert_eq!(*frame.get_pixel(75, 75), RED);
+        assert_eq!(*frame.get_pixel(51, 51), BLACK);
+        assert_eq!(*frame.get_pixel(25, 25), BLACK);
+    }
+
+    #[test]
+    fn test_draw_hidden_draws_nothing() {
+        let mut track = track();
+        track.ranges = vec![OverlayRange { start_ms: 0, end_ms: 10_000, mode: OverlayRangeMode::Hidden }];
+        let layer = OverlayLayer::new(&track, 64, 48, 200, 200);
+        let mut frame = RgbaImage::from_pixel(200, 200, BLACK);
+        layer.draw(&mut frame, &RgbaImage::from_pixel(64, 48, RED), 5000);
+        assert!(frame.pixels().all(|p| *p == BLACK));
+    }
+
+    #[test]
+    fn test_cover_viewport_crops_center() {
+        let viewport = cover_viewport(200, 100, 50, 50);
+        assert_eq!((viewport.x, viewport.y, viewport.width, viewport.height), (50.0, 0.0, 100.0, 100.0));
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use image::RgbaImage;
+
+/// Caps how much frame data the pipeline may hold in memory at once
+const MAX_BUFFERED_BYTES: usize = 1 << 30;
+
+/// How many frames may be in flight (decoded but not yet handed to the
+/// sink) so that buffered frames stay within the memory budget while still
+/// keeping every worker busy.
+pub fn max_frames_in_flight(workers: usize, bytes_per_frame: usize) -> usize {
+    let by_memory = MAX_BUFFERED_BYTES / bytes_per_frame.max(1);
+    by_memory.clamp(2, (workers * 2).max(2))
+}
+
+/// Run a bounded producer/consumer pipeline:
+///
+/// decoder thread -> worker pool (`compose`) -> reorder buffer -> `sink`
+///
+/// Frames are composed in parallel but reach the sink in decode order. At
+/// most `max_in_flight` frames exist between decoding and the sink, which
+/// bounds memory for large exports. The sink runs on the calling thread.
+/// Returns the number of frames written.
+pub fn run<F, D, C, S>(
+    mut decode: D,
+    compose: C,
+    mut sink: S,
+    workers: usize,
+    max_in_flight: usize,
+) -> Result<u64, String>
+where
+    F: Send,
+    D: FnMut() -> Result<Option<(u64, F)>, String> + Send,
+    C: Fn(&F, u64) -> RgbaImage + Sync,
+    S: FnMut(RgbaImage) -> Result<(), String>,
+{
+    let workers = workers.max(1);
+    let max_in_flight = max_in_flight.max(1);
+
+    // One token per frame allowed in flight; the decoder takes a token
+    // before decoding and the sink returns it once the frame is written
+    let (token_tx, token_rx) = mpsc::sync_channel::<()>(max_in_flight);
+    for _ in 0..max_in_flight {
+        token_tx.send(()).map_err(|_| "Pipeline closed unexpectedly")?;
+    }
+
+    let (job_tx, job_rx) = mpsc::sync_channel::<(u64, u64, F)>(max_in_flight);
+    let job_rx = Arc::new(Mutex::new(job_rx));
+    let (result_tx, result_rx) = mpsc::channel::<(u64, Result<RgbaImage, String>)>();
+    let compose = &compose;
+
+    thread::scope(|scope| {
+        let decoder = scope.spawn(move || -> Result<(), String> {
+            let mut index = 0u64;
+            // Stops early if the sink failed and dropped its token sender
+            while token_rx.recv().is_ok() {
+                match decode()? {
+                    Some((time_ms, frame)) => {
+                        if job_tx.send((index, time_ms, frame)).is_err() {
+                            break;
+                        }
+                        index += 1;
+                    }
+                    None => break,
+                }
+            }
+            Ok(())
+        });
+
+        for _ in 0..workers {
+            let job_rx = Arc::clone(&job_rx);
+            let result_tx = result_tx.clone();
+            scope.spawn(move || {
+                while let Some((index, time_ms, frame)) = next_job(&job_rx) {
+                    // A lost frame would stall the reorder buffer, so report
+                    // panics as errors instead
+                    let composed = panic::catch_unwind(AssertUnwindSafe(|| compose(&frame, time_ms)))
+                        .map_err(|_| format!("Compositing frame {} panicked", index));
+                    drop(frame);
+                    if result_tx.send((index, composed)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        // Only the workers hold these now: the decoder notices when they are
+        // gone, and the loop below ends once they have all finished
+        drop(job_rx);
+        drop(result_tx);
+
+        let written = write_in_order(result_rx, token_tx, &mut sink);
+        let decoded = decoder
+            .join()
+            .map_err(|_| "Decoder thread panicked".to_string())?;
+
+        // A sink failure is usually the root cause (e.g. the encoder died)
+        let written = written?;
+        decoded?;
+        Ok(written)
+    })
+}
+
+fn next_job<T>(jobs: &Mutex<Receiver<T>>) -> Option<T> {
+    jobs.lock().ok()?.recv().ok()
+}
+
+/// Hand composed frames to the sink in index order, releasing one in-flight
+/// token per frame written
+fn write_in_order<S>(
+    results: Receiver<(u64, Result<RgbaImage, String>)>,
+    tokens: mpsc::SyncSender<()>,
+    sink: &mut S,
+) -> Result<u64, String>
+where
+    S: FnMut(RgbaImage) -> Result<(), String>,
+{
+    let mut pending = BTreeMap::new();
+    let mut next = 0u64;
+
+    for (index, frame) in results {
+        pending.insert(index, frame?);
+        while let Some(frame) = pending.remove(&next) {
+            sink(frame)?;
+            next += 1;
+            // The decoder may already be done, in which case nobody is listening
+            tokens.try_send(()).ok();
+        }
+    }
+
+    Ok(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use image::Rgba;
+
+    fn numbered_frames(count: u64) -> impl FnMut() -> Result<Option<(u64, RgbaImage)>, String> {
+        let mut i = 0u64;
+        move || {
+            if i == count {
+                return Ok(None);
+            }
+            let frame = RgbaImage::from_pixel(1, 1, Rgba([i as u8, 0, 0, 255]));
+            i += 1;
+            Ok(Some((i * 10, frame)))
+        }
+    }
+
+    #[test]
+    fn test_max_frames_in_flight() {
+        let frame_4k = 3840 * 2160 * 4 * 2;
+        assert_eq!(max_frames_in_flight(8, frame_4k), 16);
+        assert_eq!(max_frames_in_flight(64, frame_4k), 16);
+        assert_eq!(max_frames_in_flight(4, 1024), 8);
+        assert_eq!(max_frames_in_flight(1, usize::MAX), 2);
+    }
+
+    #[test]
+    fn test_run_preserves_order() {
+        let mut seen = Vec::new();
+        let written = run(
+            numbered_frames(50),
+            |frame, _| {
+                // Later frames finish first to exercise the reorder buffer
+                let i = frame.get_pixel(0, 0)[0] as u64;
+                thread::sleep(Duration::from_micros((50 - i) * 20));
+                frame.clone()
+            },
+            |frame| {
+                seen.push(frame.get_pixel(0, 0)[0]);
+                Ok(())
+            },
+            4,
+            8,
+        )
+        .unwrap();
+
+        assert_eq!(written, 50);
+        assert_eq!(seen, (0..50).map(|i| i as u8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_run_passes_timestamps() {
+        let mut times = Vec::new();
+        run(
+            numbered_frames(3),
+            |frame, time_ms| RgbaImage::from_pixel(1, 1, Rgba([frame.get_pixel(0, 0)[0], time_ms as u8, 0, 255])),
+            |frame| {
+                times.push(frame.get_pixel(0, 0)[1]);
+                Ok(())
+            },
+            2,
+            2,
+        )
+        .unwrap();
+        assert_eq!(times, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn test_run_bounds_frames_in_flight() {
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let mut frames = numbered_frames(40);
+
+        run(
+            || {
+                let frame = frames()?;
+                if frame.is_some() {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                }
+                Ok(frame)
+            },
+            |frame, _| frame.clone(),
+            |_| {
+                thread::sleep(Duration::from_micros(200));
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            },
+            4,
+            3,
+        )
+        .unwrap();
+
+        assert!(peak.load(Ordering::SeqCst) <= 3, "Peak in flight was {}", peak.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_run_propagates_decoder_error() {
+        let mut frames = numbered_frames(10);
+        let mut calls = 0;
+        let result = run(
+            || {
+                calls += 1;
+                if calls == 5 {
+                    return Err("corrupt frame".to_string());
+                }
+                frames()
+            },
+            |frame, _| frame.clone(),
+            |_| Ok(()),
+            2,
+            4,
+        );
+        assert_eq!(result.unwrap_err(), "corrupt frame");
+    }
+
+    #[test]
+    fn test_run_reports_compose_panic() {
+        let result = run(
+            numbered_frames(10),
+            |frame, _| {
+                if frame.get_pixel(0, 0)[0] == 3 {
+                    panic!("bad frame");
+                }
+                frame.clone()
+            },
+            |_| Ok(()),
+            2,
+            4,
+        );
+        assert!(result.unwrap_err().contains("frame 3"));
+    }
+
+    #[test]
+    fn test_run_stops_on_sink_error() {
+        let decoded = AtomicUsize::new(0);
+        let mut frames = numbered_frames(1000);
+        let result = run(
+            || {
+                decoded.fetch_add(1, Ordering::SeqCst);
+                frames()
+            },
+            |frame, _| frame.clone(),
+            |_| Err("encoder exited".to_string()),
+            2,
+            4,
+        );
+        assert_eq!(result.unwrap_err(), "encoder exited");
+        assert!(decoded.load(Ordering::SeqCst) < 1000);
+    }
+}
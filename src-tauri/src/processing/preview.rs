@@ -0,0 +1,73 @@
+use std::io::Cursor;
+
+use image::imageops::{self, FilterType};
+use image::{ImageFormat, RgbaImage};
+
+use crate::models::events::RecordedEvents;
+use crate::models::project::Project;
+use crate::processing::compositor::Compositor;
+use crate::processing::decoder::{self, FrameDecoder};
+use crate::processing::overlay::OverlayReader;
+
+/// Render the frame at `time_ms` exactly as an export would, scaled to at
+/// most `max_width` pixels wide (0 for full size) and encoded as PNG
+pub fn render_preview_frame(
+    project: &Project,
+    events: &RecordedEvents,
+    time_ms: u64,
+    max_width: u32,
+) -> Result<Vec<u8>, String> {
+    let fps = project.fps.max(1.0);
+    let mut decoder = FrameDecoder::open_at(&project.video_path, fps, time_ms)?;
+    let (_, frame) = decoder
+        .next_frame()?
+        .ok_or_else(|| format!("No frame at {}ms", time_ms))?;
+
+    let overlay_frame = match &project.overlay {
+        Some(track) => OverlayReader::open(track, fps, decoder.width(), time_ms)?.frame_at(time_ms)?,
+        None => None,
+    };
+    let overlay_size = overlay_frame.as_ref().map(|frame| frame.dimensions());
+
+    let compositor = Compositor::new(project, events, decoder.width(), decoder.height(), fps, overlay_size)?;
+    let canvas = compositor.compose_frame(&frame, overlay_frame.as_ref(), time_ms);
+    encode_png(&canvas, max_width)
+}
+
+/// Downscale the composed canvas if needed and encode it as PNG. Scaling
+/// happens after compositing so padding and corners keep their proportions.
+fn encode_png(canvas: &RgbaImage, max_width: u32) -> Result<Vec<u8>, String> {
+    let (width, height) = decoder::scaled_dimensions(canvas.width(), canvas.height(), max_width);
+    let scaled;
+    let image = if (width, height) == canvas.dimensions() {
+        canvas
+    } else {
+        scaled = imageops::resize(canvas, width, height, FilterType::Triangle);
+        &scaled
+    };
+
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode preview: {}", e))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    #[test]
+    fn test_encode_png_scales_to_max_width() {
+        let canvas = RgbaImage::from_pixel(400, 200, Rgba([10, 20, 30, 255]));
+
+        let scaled = image::load_from_memory(&encode_png(&canvas, 100).unwrap()).unwrap().to_rgba8();
+        assert_eq!(scaled.dimensions(), (100, 50));
+        assert_eq!(*scaled.get_pixel(50, 25), Rgba([10, 20, 30, 255]));
+
+        let full = image::load_from_memory(&encode_png(&canvas, 0).unwrap()).unwrap();
+        assert_eq!((full.width(), full.height()), (400, 200));
+    }
+}
@@ -0,0 +1,291 @@
+use std::borrow::Cow;
+
+use image::imageops;
+use image::{Rgba, RgbaImage};
+
+use crate::models::redactions::{Redaction, RedactionMode};
+use crate::processing::background::parse_hex_color;
+use crate::processing::layer::{FrameContext, Layer, Surfaces};
+
+/// A redaction resolved to whole source pixels
+struct PreparedRedaction {
+    start_ms: u64,
+    end_ms: u64,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    effect: Effect,
+}
+
+enum Effect {
+    Blur(f32),
+    Pixelate(u32),
+    Fill(Rgba<u8>),
+}
+
+/// Obscures redacted regions of the source frame before it is zoomed, so
+/// they stay covered at every zoom level
+pub struct RedactionLayer {
+    redactions: Vec<PreparedRedaction>,
+}
+
+impl RedactionLayer {
+    /// Unlike annotations, a redaction that can't be applied is an error
+    /// rather than silently skipped
+    pub fn new(redactions: &[Redaction], source_width: u32, source_height: u32) -> Result<Self, String> {
+        let mut prepared = Vec::with_capacity(redactions.len());
+        for redaction in redactions {
+            prepared.push(prepare(redaction, source_width, source_height)?);
+        }
+        Ok(Self { redactions: prepared })
+    }
+
+    /// Like `new`, for frames decoded at `width`x`height` rather than the
+    /// full source size, such as thumbnails
+    pub fn scaled(
+        redactions: &[Redaction],
+        source_width: u32,
+        source_height: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, String> {
+        let sx = width as f64 / source_width.max(1) as f64;
+        let sy = height as f64 / source_height.max(1) as f64;
+        let scaled: Vec<Redaction> = redactions
+            .iter()
+            .map(|r| Redaction {
+                x: r.x * sx,
+                y: r.y * sy,
+                width: r.width * sx,
+                height: r.height * sy,
+                mode: match &r.mode {
+                    RedactionMode::Blur { sigma } => RedactionMode::Blur { sigma: sigma * sx },
+                    RedactionMode::Pixelate { block_size } => RedactionMode::Pixelate {
+                        block_size: (*block_size as f64 * sx).round().max(1.0) as u32,
+                    },
+                    mode => mode.clone(),
+                },
+                ..r.clone()
+            })
+            .collect();
+        Self::new(&scaled, width, height)
+    }
+
+    /// The frame with every redaction active at `time_ms` applied. Borrows
+    /// the frame unchanged when none are active.
+    pub fn apply<'a>(&self, frame: &'a RgbaImage, time_ms: u64) -> Cow<'a, RgbaImage> {
+        let mut active = self
+            .redactions
+            .iter()
+            .filter(|r| time_ms >= r.start_ms && time_ms < r.end_ms)
+            .peekable();
+        if active.peek().is_none() {
+            return Cow::Borrowed(frame);
+        }
+
+        let mut frame = frame.clone();
+        for redaction in active {
+            match redaction.effect {
+                Effect::Blur(sigma) => blur(&mut frame, redaction, sigma),
+                Effect::Pixelate(block_size) => pixelate(&mut frame, redaction, block_size),
+                Effect::Fill(color) => fill(&mut frame, redaction, color),
+            }
+        }
+        Cow::Owned(frame)
+    }
+}
+
+impl Layer for RedactionLayer {
+    fn render(&self, surfaces: &mut Surfaces, ctx: &FrameContext) {
+        if let Cow::Owned(redacted) = self.apply(&surfaces.source, ctx.time_ms) {
+            surfaces.source = Cow::Owned(redacted);
+        }
+    }
+}
+
+fn prepare(redaction: &Redaction, source_width: u32, source_height: u32) -> Result<PreparedRedaction, String> {
+    let id = &redaction.id;
+    if redaction.end_ms <= redaction.start_ms {
+        return Err(format!("Redaction {} ends before it starts", id));
+    }
+    let (x, y, width, height) = (redaction.x, redaction.y, redaction.width, redaction.height);
+    if !(x.is_finite() && y.is_finite() && width > 0.0 && height > 0.0 && width.is_finite() && height.is_finite()) {
+        return Err(format!("Redaction {} has an invalid rectangle", id));
+    }
+
+    // Round outwards so partially covered pixels are hidden too
+    let left = x.floor().max(0.0);
+    let top = y.floor().max(0.0);
+    let right = (x + width).ceil().min(source_width as f64);
+    let bottom = (y + height).ceil().min(source_height as f64);
+    if right <= left || bottom <= top {
+        return Err(format!(
+            "Redaction {} lies outside the {}x{} recording",
+            id, source_width, source_height
+        ));
+    }
+
+    let effect = match &redaction.mode {
+        RedactionMode::Blur { sigma } if *sigma > 0.0 => Effect::Blur(*sigma as f32),
+        RedactionMode::Blur { .. } => return Err(format!("Redaction {} needs a positive blur sigma", id)),
+        RedactionMode::Pixelate { block_size } if *block_size > 0 => Effect::Pixelate(*block_size),
+        RedactionMode::Pixelate { .. } => return Err(format!("Redaction {} needs a positive block size", id)),
+        RedactionMode::Fill { color } => {
+            let mut color = parse_hex_color(color);
+            color[3] = 255;
+            Effect::Fill(color)
+        }
+    };
+
+    Ok(PreparedRedaction {
+        start_ms: redaction.start_ms,
+        end_ms: redaction.end_ms,
+        x: left as u32,
+        y: top as u32,
+        width: (right - left) as u32,
+        height: (bottom - top) as u32,
+        effect,
+    })
+}
+
+/// Blur a crop padded by the kernel's reach, so the region's edges blend into
+/// their surroundings, and copy back only the redacted pixels
+fn blur(frame: &mut RgbaImage, r: &PreparedRedaction, sigma: f32) {
+    let reach = (sigma * 3.0).ceil() as u32;
+    let left = r.x.saturating_sub(reach);
+    let top = r.y.saturating_sub(reach);
+    let right = (r.x + r.width + reach).min(frame.width());
+    let bottom = (r.y + r.height + reach).min(frame.height());
+
+    let crop = imageops::crop_imm(frame, left, top, right - left, bottom - top).to_image();
+    let blurred = imageops::blur(&crop, sigma);
+    for y in 0..r.height {
+        for x in 0..r.width {
+            let pixel = *blurred.get_pixel(r.x - left + x, r.y - top + y);
+            frame.put_pixel(r.x + x, r.y + y, pixel);
+        }
+    }
+}
+
+/// Replace each block with its average color. Blocks are aligned to the
+/// region's top-left corner and clipped at its far edges.
+fn pixelate(frame: &mut RgbaImage, r: &PreparedRedaction, block_size: u32) {
+    for block_y in (r.y..r.y + r.height).step_by(block_size as usize) {
+        let block_bottom = (block_y + block_size).min(r.y + r.height);
+        for block_x in (r.x..r.x + r.width).step_by(block_size as usize) {
+            let block_right = (block_x + block_size).min(r.x + r.width);
+
+            let mut sum = [0u64; 4];
+            for y in block_y..block_bottom {
+                for x in block_x..block_right {
+                    let pixel = frame.get_pixel(x, y);
+                    for (acc, &value) in sum.iter_mut().zip(pixel.0.iter()) {
+                        *acc += value as u64;
+                    }
+                }
+            }
+            let count = ((block_right - block_x) * (block_bottom - block_y)) as u64;
+            let average = Rgba(sum.map(|acc| ((acc + count / 2) / count) as u8));
+
+            for y in block_y..block_bottom {
+                for x in block_x..block_right {
+                    frame.put_pixel(x, y, average);
+                }
+            }
+        }
+    }
+}
+
+fn fill(frame: &mut RgbaImage, r: &PreparedRedaction, color: Rgba<u8>) {
+    for y in r.y..r.y + r.height {
+        for x in r.x..r.x + r.width {
+            frame.put_pixel(x, y, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+    fn redaction(x: f64, y: f64, width: f64, height: f64, mode: RedactionMode) -> Redaction {
+        Redaction { id: "r1".to_string(), start_ms: 1000, end_ms: 2000, x, y, width, height, mode }
+    }
+
+    /// Vertical black and white stripes, one pixel wide
+    fn stripes(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, _| if x % 2 == 0 { Rgba([0, 0, 0, 255]) } else { WHITE })
+    }
+
+    #[test]
+    fn test_inactive_borrows_frame() {
+        let fill = RedactionMode::Fill { color: "#ff0000".to_string() };
+        let layer = RedactionLayer::new(&[redaction(0.0, 0.0, 10.0, 10.0, fill)], 100, 100).unwrap();
+        let frame = stripes(100, 100);
+        assert!(matches!(layer.apply(&frame, 999), Cow::Borrowed(_)));
+        assert!(matches!(layer.apply(&frame, 2000), Cow::Borrowed(_)));
+        assert!(matches!(layer.apply(&frame, 1000), Cow::Owned(_)));
+    }
+
+    #[test]
+    fn test_fill_rounds_outwards() {
+        let fill = RedactionMode::Fill { color: "#ff0000".to_string() };
+        let layer = RedactionLayer::new(&[redaction(10.5, 10.5, 9.0, 9.0, fill)], 100, 100).unwrap();
+        let frame = layer.apply(&stripes(100, 100), 1500).into_owned();
+        assert_eq!(*frame.get_pixel(10, 10), Rgba([255, 0, 0, 255]));
+        assert_eq!(*frame.get_pixel(19, 19), Rgba([255, 0, 0, 255]));
+        assert_eq!(*frame.get_pixel(20, 20), Rgba([0, 0, 0, 255]));
+        assert_eq!(*frame.get_pixel(9, 9), WHITE);
+    }
+
+    #[test]
+    fn test_scaled_covers_the_same_region() {
+        let fill = RedactionMode::Fill { color: "#ff0000".to_string() };
+        let layer = RedactionLayer::scaled(&[redaction(40.0, 20.0, 20.0, 20.0, fill)], 200, 100, 100, 50).unwrap();
+        let frame = layer.apply(&stripes(100, 50), 1500).into_owned();
+        assert_eq!(*frame.get_pixel(20, 10), Rgba([255, 0, 0, 255]));
+        assert_eq!(*frame.get_pixel(29, 19), Rgba([255, 0, 0, 255]));
+        assert_eq!(*frame.get_pixel(30, 20), Rgba([0, 0, 0, 255]));
+        assert_eq!(*frame.get_pixel(19, 9), WHITE);
+    }
+
+    #[test]
+    fn test_pixelate_averages_blocks() {
+        let pixelate = RedactionMode::Pixelate { block_size: 4 };
+        let layer = RedactionLayer::new(&[redaction(20.0, 20.0, 10.0, 10.0, pixelate)], 100, 100).unwrap();
+        let frame = layer.apply(&stripes(100, 100), 1500).into_owned();
+        // Full blocks hold two black and two white columns
+        assert_eq!(*frame.get_pixel(21, 21), Rgba([128, 128, 128, 255]));
+        // The clipped last block holds one of each
+        assert_eq!(*frame.get_pixel(29, 29), Rgba([128, 128, 128, 255]));
+        assert_eq!(*frame.get_pixel(31, 21), WHITE);
+    }
+
+    #[test]
+    fn test_blur_only_changes_region() {
+        let blur = RedactionMode::Blur { sigma: 4.0 };
+        let layer = RedactionLayer::new(&[redaction(40.0, 40.0, 20.0, 20.0, blur)], 100, 100).unwrap();
+        let original = stripes(100, 100);
+        let frame = layer.apply(&original, 1500);
+
+        let center = frame.get_pixel(50, 50)[0];
+        assert!((100..=155).contains(&center), "got {}", center);
+        assert_eq!(frame.get_pixel(39, 50), original.get_pixel(39, 50));
+        assert_eq!(frame.get_pixel(60, 50), original.get_pixel(60, 50));
+    }
+
+    #[test]
+    fn test_unusable_redactions_are_errors() {
+        let blur = RedactionMode::default();
+        assert!(RedactionLayer::new(&[redaction(200.0, 0.0, 10.0, 10.0, blur.clone())], 100, 100).is_err());
+        assert!(RedactionLayer::new(&[redaction(0.0, 0.0, 0.0, 10.0, blur.clone())], 100, 100).is_err());
+        assert!(RedactionLayer::new(&[redaction(0.0, 0.0, 10.0, 10.0, RedactionMode::Blur { sigma: 0.0 })], 100, 100).is_err());
+
+        let mut backwards = redaction(0.0, 0.0, 10.0, 10.0, blur);
+        backwards.end_ms = backwards.start_ms;
+        assert!(RedactionLayer::new(&[backwards], 100, 100).is_err());
+    }
+}
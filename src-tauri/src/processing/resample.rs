@@ -0,0 +1,251 @@
+use image::RgbaImage;
+
+use crate::processing::zoom::FrameViewport;
+
+/// Resample the (fractional) viewport of `source` into a `width` x `height`
+/// image with one resampling step.
+///
+/// Each output pixel maps back into the source through the viewport's
+/// scale and offset, so a viewport that moves by a fraction of a pixel
+/// produces a correspondingly fractional shift instead of snapping to the
+/// pixel grid. Uses a separable Catmull-Rom (bicubic) filter, widened when
+/// minifying so downscaled previews do not alias.
+pub fn sample_viewport(
+    source: &RgbaImage,
+    viewport: &FrameViewport,
+    width: u32,
+    height: u32,
+) -> RgbaImage {
+    let is_identity = viewport.x == 0.0
+        && viewport.y == 0.0
+        && viewport.width == width as f64
+        && viewport.height == height as f64
+        && source.width() == width
+        && source.height() == height;
+    if is_identity {
+        return source.clone();
+    }
+
+    let columns = AxisFilter::new(viewport.x, viewport.width, width, source.width());
+    let rows = AxisFilter::new(viewport.y, viewport.height, height, source.height());
+
+    let out_w = width as usize;
+    let src_w = source.width() as usize;
+    let src = source.as_raw();
+
+    // Source rows filtered horizontally, kept in a ring. Each output row's
+    // vertical taps span at most `taps_per_pixel` consecutive source rows,
+    // and that window only moves down, so a ring that size never evicts a
+    // row still needed and keeps memory to a few rows per frame.
+    let ring_len = rows.taps_per_pixel;
+    let mut ring = vec![0f32; ring_len * out_w * 4];
+    let mut ring_rows = vec![u32::MAX; ring_len];
+    let mut taps: Vec<(usize, f32)> = Vec::with_capacity(ring_len);
+
+    let mut output = vec![0u8; out_w * height as usize * 4];
+    for (y, out_row) in output.chunks_exact_mut(out_w * 4).enumerate() {
+        taps.clear();
+        for (&src_y, &weight) in rows.taps(y) {
+            let slot = src_y as usize % ring_len;
+            if ring_rows[slot] != src_y {
+                let src_row = &src[src_y as usize * src_w * 4..][..src_w * 4];
+                filter_row(src_row, &columns, &mut ring[slot * out_w * 4..][..out_w * 4]);
+                ring_rows[slot] = src_y;
+            }
+            taps.push((slot * out_w * 4, weight));
+        }
+
+        // Vertical pass: filtered rows -> output row
+        for (i, out) in out_row.iter_mut().enumerate() {
+            let value: f32 = taps.iter().map(|&(row, weight)| ring[row + i] * weight).sum();
+            *out = value.round().clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    RgbaImage::from_raw(width, height, output).expect("buffer matches output size")
+}
+
+/// Horizontal pass: one source row -> output columns
+fn filter_row(src_row: &[u8], columns: &AxisFilter, out_row: &mut [f32]) {
+    for (x, out) in out_row.chunks_exact_mut(4).enumerate() {
+        let mut acc = [0f32; 4];
+        for (&index, &weight) in columns.taps(x) {
+            let px = &src_row[index as usize * 4..][..4];
+            for c in 0..4 {
+                acc[c] += px[c] as f32 * weight;
+            }
+        }
+        out.copy_from_slice(&acc);
+    }
+}
+
+/// Precomputed filter taps mapping one output axis back onto the source
+struct AxisFilter {
+    taps_per_pixel: usize,
+    /// Source index for each tap, clamped to the edge
+    indices: Vec<u32>,
+    weights: Vec<f32>,
+}
+
+impl AxisFilter {
+    fn new(offset: f64, extent: f64, out_len: u32, src_len: u32) -> Self {
+        let step = extent / out_len.max(1) as f64;
+        // Stretch the kernel when minifying so every source pixel contributes
+        let support_scale = step.max(1.0);
+        let radius = 2.0 * support_scale;
+        let taps_per_pixel = (radius * 2.0).ceil() as usize + 1;
+        let max_index = src_len.saturating_sub(1) as i64;
+
+        let mut indices = Vec::with_capacity(out_len as usize * taps_per_pixel);
+        let mut weights = Vec::with_capacity(out_len as usize * taps_per_pixel);
+
+        for i in 0..out_len {
+            // Source position of the output pixel's center
+            let center = offset + (i as f64 + 0.5) * step - 0.5;
+            let first = (center - radius).floor() as i64 + 1;
+
+            let start = weights.len();
+            let mut total = 0.0;
+            for j in 0..taps_per_pixel as i64 {
+                let index = first + j;
+                let weight = catmull_rom((index as f64 - center) / support_scale);
+                indices.push(index.clamp(0, max_index) as u32);
+                weights.push(weight as f32);
+                total += weight;
+            }
+            // Normalize so flat regions stay exactly flat
+            if total.abs() > f64::EPSILON {
+                for weight in &mut weights[start..] {
+                    *weight /= total as f32;
+                }
+            }
+        }
+
+        Self {
+            taps_per_pixel,
+            indices,
+            weights,
+        }
+    }
+
+    fn taps(&self, i: usize) -> impl Iterator<Item = (&u32, &f32)> {
+        let range = i * self.taps_per_pixel..(i + 1) * self.taps_per_pixel;
+        self.indices[range.clone()].iter().zip(&self.weights[range])
+    }
+}
+
+/// Catmull-Rom cubic (B = 0, C = 0.5)
+fn catmull_rom(x: f64) -> f64 {
+    let x = x.abs();
+    if x < 1.0 {
+        1.5 * x * x * x - 2.5 * x * x + 1.0
+    } else if x < 2.0 {
+        -0.5 * x * x * x + 2.5 * x * x - 4.0 * x + 2.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn viewport(x: f64, y: f64, width: f64, height: f64) -> FrameViewport {
+        FrameViewport {
+            x,
+            y,
+            width,
+            height,
+            zoom: 1.0,
+            center_x: x + width / 2.0,
+            center_y: y + height / 2.0,
+        }
+    }
+
+    /// Horizontal ramp where each column's red value equals its x coordinate
+    fn ramp(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, _| Rgba([x as u8, 0, 0, 255]))
+    }
+
+    #[test]
+    fn test_identity_returns_source() {
+        let source = ramp(64, 32);
+        let result = sample_viewport(&source, &viewport(0.0, 0.0, 64.0, 32.0), 64, 32);
+        assert_eq!(result, source);
+    }
+
+    #[test]
+    fn test_flat_color_stays_flat() {
+        let source = RgbaImage::from_pixel(50, 40, Rgba([12, 200, 99, 255]));
+        let result = sample_viewport(&source, &viewport(3.3, 7.7, 21.1, 16.9), 50, 40);
+        assert!(result.pixels().all(|p| *p == Rgba([12, 200, 99, 255])));
+    }
+
+    #[test]
+    fn test_integer_shift_is_exact() {
+        let source = ramp(100, 10);
+        let result = sample_viewport(&source, &viewport(10.0, 0.0, 50.0, 10.0), 50, 10);
+        for x in 0..50 {
+            assert_eq!(result.get_pixel(x, 5)[0], 10 + x as u8);
+        }
+    }
+
+    #[test]
+    fn test_fractional_shift_is_not_snapped() {
+        // Moving the viewport by a quarter pixel must move the image by a
+        // quarter pixel, not zero or one
+        let source = RgbaImage::from_fn(60, 4, |x, _| Rgba([(x * 4) as u8, 0, 0, 255]));
+        let at = |offset: f64| {
+            sample_viewport(&source, &viewport(offset, 0.0, 30.0, 4.0), 30, 4).get_pixel(15, 2)[0]
+        };
+        let values: Vec<u8> = [10.0, 10.25, 10.5, 10.75, 11.0].iter().map(|&o| at(o)).collect();
+        assert_eq!(values, vec![100, 101, 102, 103, 104]);
+    }
+
+    #[test]
+    fn test_zoom_in_is_smooth() {
+        // A 2x zoom of a ramp is a ramp with half the slope: no repeated steps
+        let source = RgbaImage::from_fn(60, 4, |x, _| Rgba([(x * 4) as u8, 0, 0, 255]));
+        let result = sample_viewport(&source, &viewport(15.0, 0.0, 30.0, 2.0), 60, 4);
+        for x in 5..50 {
+            let a = result.get_pixel(x, 1)[0] as i32;
+            let b = result.get_pixel(x + 1, 1)[0] as i32;
+            assert_eq!(b - a, 2, "Step at x={}", x);
+        }
+    }
+
+    #[test]
+    fn test_vertical_zoom_is_smooth() {
+        // Same as above down the rows, which go through the row ring
+        let source = RgbaImage::from_fn(4, 60, |_, y| Rgba([(y * 4) as u8, 0, 0, 255]));
+        let result = sample_viewport(&source, &viewport(0.0, 15.0, 2.0, 30.0), 4, 60);
+        for y in 5..50 {
+            let a = result.get_pixel(1, y)[0] as i32;
+            let b = result.get_pixel(1, y + 1)[0] as i32;
+            assert_eq!(b - a, 2, "Step at y={}", y);
+        }
+    }
+
+    #[test]
+    fn test_downscale_averages() {
+        // Alternating columns average to mid-grey instead of aliasing
+        let source = RgbaImage::from_fn(64, 8, |x, _| {
+            let v = if x % 2 == 0 { 0 } else { 255 };
+            Rgba([v, v, v, 255])
+        });
+        let result = sample_viewport(&source, &viewport(0.0, 0.0, 64.0, 8.0), 16, 2);
+        for x in 2..14 {
+            let v = result.get_pixel(x, 1)[0] as i32;
+            assert!((v - 128).abs() <= 8, "Column {} was {}", x, v);
+        }
+    }
+
+    #[test]
+    fn test_edges_clamp_to_source() {
+        let source = ramp(20, 20);
+        let result = sample_viewport(&source, &viewport(0.0, 0.0, 10.0, 10.0), 20, 20);
+        assert_eq!(result.dimensions(), (20, 20));
+        assert!(result.get_pixel(0, 0)[0] <= 1);
+    }
+}
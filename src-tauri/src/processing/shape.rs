@@ -0,0 +1,248 @@
+use image::{Rgba, RgbaImage};
+
+use crate::processing::background::{rounded_rect_sdf, Corners, FrameRect};
+use crate::processing::blend::alpha_blend;
+
+/// Samples per axis when rasterizing polygons
+const POLYGON_SAMPLES: u32 = 4;
+
+/// An anti-aliased rounded rectangle at sub-pixel coordinates
+#[derive(Debug, Clone, Copy)]
+pub struct Shape {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub corners: Corners,
+}
+
+impl Shape {
+    pub fn rect(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self { x, y, width, height, corners: Corners { radius: 0.0, exponent: 2.0 } }
+    }
+
+    pub fn rounded(x: f64, y: f64, width: f64, height: f64, radius: f64) -> Self {
+        Self { x, y, width, height, corners: Corners { radius, exponent: 2.0 } }
+    }
+
+    pub fn circle(center_x: f64, center_y: f64, radius: f64) -> Self {
+        Self::rounded(center_x - radius, center_y - radius, radius * 2.0, radius * 2.0, radius)
+    }
+
+    pub fn from_rect(rect: &FrameRect, corners: Corners) -> Self {
+        Self {
+            x: rect.x as f64,
+            y: rect.y as f64,
+            width: rect.width as f64,
+            height: rect.height as f64,
+            corners,
+        }
+    }
+
+    pub fn fill(&self, canvas: &mut RgbaImage, color: Rgba<u8>) {
+        self.fill_above(canvas, color, f64::INFINITY);
+    }
+
+    /// Fill only the rows above `bottom`
+    pub fn fill_above(&self, canvas: &mut RgbaImage, color: Rgba<u8>, bottom: f64) {
+        if self.width <= 0.0 || self.height <= 0.0 {
+            return;
+        }
+        let bottom = (self.y + self.height).min(bottom);
+        for_each_pixel(canvas, (self.x, self.y, self.x + self.width, bottom), |px, py| {
+            (0.5 - self.distance(px, py)).clamp(0.0, 1.0)
+        }, color);
+    }
+
+    /// Outline the shape with a line of `width` centered on its edge
+    pub fn stroke(&self, canvas: &mut RgbaImage, color: Rgba<u8>, width: f64) {
+        if self.width <= 0.0 || self.height <= 0.0 || width <= 0.0 {
+            return;
+        }
+        let half = width / 2.0;
+        let bounds = (self.x - half, self.y - half, self.x + self.width + half, self.y + self.height + half);
+        for_each_pixel(canvas, bounds, |px, py| {
+            (0.5 - (self.distance(px, py).abs() - half)).clamp(0.0, 1.0)
+        }, color);
+    }
+
+    fn distance(&self, px: f64, py: f64) -> f64 {
+        rounded_rect_sdf(px - self.x, py - self.y, self.width, self.height, self.corners)
+    }
+}
+
+/// Draw a straight line with round caps
+pub fn draw_line(canvas: &mut RgbaImage, from: (f64, f64), to: (f64, f64), width: f64, color: Rgba<u8>) {
+    if width <= 0.0 {
+        return;
+    }
+    let half = width / 2.0;
+    let bounds = (
+        from.0.min(to.0) - half,
+        from.1.min(to.1) - half,
+        from.0.max(to.0) + half,
+        from.1.max(to.1) + half,
+    );
+    for_each_pixel(canvas, bounds, |px, py| {
+        (0.5 - (distance_to_segment(px, py, from, to) - half)).clamp(0.0, 1.0)
+    }, color);
+}
+
+/// Fill a polygon, anti-aliased by supersampling
+pub fn fill_polygon(canvas: &mut RgbaImage, points: &[(f64, f64)], color: Rgba<u8>) {
+    if points.len() < 3 {
+        return;
+    }
+    let bounds = points.iter().fold(
+        (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        |(x0, y0, x1, y1), &(x, y)| (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+    );
+    let step = 1.0 / POLYGON_SAMPLES as f64;
+    for_each_pixel(canvas, bounds, |px, py| {
+        let mut inside = 0;
+        for sy in 0..POLYGON_SAMPLES {
+            for sx in 0..POLYGON_SAMPLES {
+                let x = px - 0.5 + (sx as f64 + 0.5) * step;
+                let y = py - 0.5 + (sy as f64 + 0.5) * step;
+                if point_in_polygon(x, y, points) {
+                    inside += 1;
+                }
+            }
+        }
+        inside as f64 / (POLYGON_SAMPLES * POLYGON_SAMPLES) as f64
+    }, color);
+}
+
+/// Even-odd point in polygon test
+pub fn point_in_polygon(x: f64, y: f64, points: &[(f64, f64)]) -> bool {
+    let mut inside = false;
+    let mut j = points.len() - 1;
+    for i in 0..points.len() {
+        let (xi, yi) = points[i];
+        let (xj, yj) = points[j];
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+pub fn distance_to_segment(x: f64, y: f64, a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (abx, aby) = (b.0 - a.0, b.1 - a.1);
+    let len_sq = abx * abx + aby * aby;
+    let t = if len_sq > 0.0 {
+        (((x - a.0) * abx + (y - a.1) * aby) / len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (dx, dy) = (x - (a.0 + abx * t), y - (a.1 + aby * t));
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Blend `color` over every canvas pixel in `bounds` (x0, y0, x1, y1),
+/// weighted by the coverage computed at the pixel's center
+fn for_each_pixel(
+    canvas: &mut RgbaImage,
+    bounds: (f64, f64, f64, f64),
+    coverage: impl Fn(f64, f64) -> f64,
+    color: Rgba<u8>,
+) {
+    let (x0, y0, x1, y1) = bounds;
+    let x0 = x0.floor().max(0.0) as u32;
+    let y0 = y0.floor().max(0.0) as u32;
+    let x1 = (x1.ceil().max(0.0) as u32).min(canvas.width());
+    let y1 = (y1.ceil().max(0.0) as u32).min(canvas.height());
+
+    for py in y0..y1 {
+        for px in x0..x1 {
+            let c = coverage(px as f64 + 0.5, py as f64 + 0.5);
+            if c <= 0.0 {
+                continue;
+            }
+            let alpha = (c * color[3] as f64).round() as u8;
+            if alpha == 0 {
+                continue;
+            }
+            let fg = Rgba([color[0], color[1], color[2], alpha]);
+            let blended = alpha_blend(canvas.get_pixel(px, py), &fg);
+            canvas.put_pixel(px, py, blended);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+    fn black(size: u32) -> RgbaImage {
+        RgbaImage::from_pixel(size, size, Rgba([0, 0, 0, 255]))
+    }
+
+    #[test]
+    fn test_fill_is_antialiased() {
+        let mut canvas = black(20);
+        Shape::circle(10.0, 10.0, 6.0).fill(&mut canvas, WHITE);
+        assert_eq!(canvas.get_pixel(10, 10)[0], 255);
+        assert_eq!(canvas.get_pixel(1, 1)[0], 0);
+        assert!((1..19).any(|x| {
+            let v = canvas.get_pixel(x, 5)[0];
+            v > 0 && v < 255
+        }));
+    }
+
+    #[test]
+    fn test_fill_above_clips_rows() {
+        let mut canvas = RgbaImage::new(10, 10);
+        Shape::rect(0.0, 0.0, 10.0, 10.0).fill_above(&mut canvas, Rgba([255, 0, 0, 255]), 4.0);
+        assert_eq!(canvas.get_pixel(5, 3)[0], 255);
+        assert_eq!(canvas.get_pixel(5, 4)[0], 0);
+    }
+
+    #[test]
+    fn test_stroke_leaves_interior() {
+        let mut canvas = black(30);
+        Shape::rect(5.0, 5.0, 20.0, 20.0).stroke(&mut canvas, WHITE, 2.0);
+        assert_eq!(canvas.get_pixel(5, 15)[0], 255);
+        assert_eq!(canvas.get_pixel(4, 15)[0], 255);
+        assert_eq!(canvas.get_pixel(15, 15)[0], 0);
+        assert_eq!(canvas.get_pixel(1, 15)[0], 0);
+    }
+
+    #[test]
+    fn test_draw_line() {
+        let mut canvas = black(30);
+        draw_line(&mut canvas, (5.0, 15.5), (25.0, 15.5), 3.0, WHITE);
+        assert_eq!(canvas.get_pixel(15, 15)[0], 255);
+        assert_eq!(canvas.get_pixel(15, 10)[0], 0);
+        // Round caps reach just past the end points
+        assert_eq!(canvas.get_pixel(25, 15)[0], 255);
+        assert_eq!(canvas.get_pixel(28, 15)[0], 0);
+    }
+
+    #[test]
+    fn test_fill_polygon() {
+        let mut canvas = black(20);
+        fill_polygon(&mut canvas, &[(0.0, 0.0), (20.0, 0.0), (0.0, 20.0)], WHITE);
+        assert_eq!(canvas.get_pixel(3, 3)[0], 255);
+        assert_eq!(canvas.get_pixel(16, 16)[0], 0);
+        // Pixels on the diagonal are partially covered
+        let diagonal = canvas.get_pixel(10, 9)[0];
+        assert!(diagonal > 0 && diagonal < 255);
+    }
+
+    #[test]
+    fn test_point_in_polygon() {
+        let square = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        assert!(point_in_polygon(5.0, 5.0, &square));
+        assert!(!point_in_polygon(15.0, 5.0, &square));
+    }
+
+    #[test]
+    fn test_distance_to_segment() {
+        assert_eq!(distance_to_segment(5.0, 3.0, (0.0, 0.0), (10.0, 0.0)), 3.0);
+        assert_eq!(distance_to_segment(13.0, 4.0, (0.0, 0.0), (10.0, 0.0)), 5.0);
+    }
+}
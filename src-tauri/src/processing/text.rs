@@ -0,0 +1,203 @@
+use std::path::Path;
+
+use ab_glyph::{point, Font as _, FontVec, PxScale, ScaleFont};
+use image::{Rgba, RgbaImage};
+
+use crate::processing::blend::alpha_blend;
+
+/// Fonts tried, in order, when no font file is specified
+const SYSTEM_FONT_PATHS: &[&str] = &[
+    "/System/Library/Fonts/SFNS.ttf",
+    "/System/Library/Fonts/Helvetica.ttc",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial.ttf",
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+];
+
+/// DejaVu Sans, used when none of the system fonts can be loaded
+const BUNDLED_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+
+/// A loaded font that can measure and draw single lines of text
+pub struct Font {
+    font: FontVec,
+}
+
+impl Font {
+    /// Load a .ttf, .otf or the first face of a .ttc file
+    pub fn load(path: &str) -> Result<Self, String> {
+        let data = std::fs::read(path).map_err(|e| format!("Failed to read font {}: {}", path, e))?;
+        let font = FontVec::try_from_vec_and_index(data, 0)
+            .map_err(|e| format!("Failed to parse font {}: {}", path, e))?;
+        Ok(Self { font })
+    }
+
+    /// The font shipped with the app, which is always available
+    pub fn bundled() -> Self {
+        let font = FontVec::try_from_vec(BUNDLED_FONT.to_vec()).expect("bundled font parses");
+        Self { font }
+    }
+
+    /// Load the first available system UI font, or the bundled one
+    pub fn system() -> Self {
+        SYSTEM_FONT_PATHS
+            .iter()
+            .filter(|path| Path::new(path).is_file())
+            .find_map(|path| Self::load(path).ok())
+            .unwrap_or_else(|| {
+                log::warn!("No usable system font found, using the bundled font");
+                Self::bundled()
+            })
+    }
+
+    /// Whether the font has a glyph for every character of `text`
+    pub fn has_glyphs(&self, text: &str) -> bool {
+        text.chars().all(|c| self.font.glyph_id(c).0 != 0)
+    }
+
+    /// Height of a line of text at `size` pixels
+    pub fn line_height(&self, size: f64) -> f64 {
+        let scaled = self.font.as_scaled(PxScale::from(size as f32));
+        (scaled.ascent() - scaled.descent()) as f64
+    }
+
+    /// Advance width of `text` at `size` pixels, including kerning
+    pub fn text_width(&self, text: &str, size: f64) -> f64 {
+        let scaled = self.font.as_scaled(PxScale::from(size as f32));
+        let mut width = 0.0f32;
+        let mut previous = None;
+        for c in text.chars() {
+            let id = scaled.glyph_id(c);
+            if let Some(prev) = previous {
+                width += scaled.kern(prev, id);
+            }
+            width += scaled.h_advance(id);
+            previous = Some(id);
+        }
+        width as f64
+    }
+
+    /// Shorten `text` with an ellipsis until it fits in `max_width`
+    pub fn truncate(&self, text: &str, size: f64, max_width: f64) -> String {
+        if self.text_width(text, size) <= max_width {
+            return text.to_string();
+        }
+        let mut chars: Vec<char> = text.chars().collect();
+        while !chars.is_empty() {
+            chars.pop();
+            let candidate: String = chars.iter().collect::<String>() + "\u{2026}";
+            if self.text_width(&candidate, size) <= max_width {
+                return candidate;
+            }
+        }
+        String::new()
+    }
+
+    /// Draw a single line of text with its top-left corner at (x, y)
+    pub fn draw_text(&self, canvas: &mut RgbaImage, text: &str, x: f64, y: f64, size: f64, color: Rgba<u8>) {
+        let scale = PxScale::from(size as f32);
+        let scaled = self.font.as_scaled(scale);
+        let baseline = y as f32 + scaled.ascent();
+        let (cw, ch) = (canvas.width() as i64, canvas.height() as i64);
+
+        let mut caret = x as f32;
+        let mut previous = None;
+        for c in text.chars() {
+            let id = scaled.glyph_id(c);
+            if let Some(prev) = previous {
+                caret += scaled.kern(prev, id);
+            }
+            let glyph = id.with_scale_and_position(scale, point(caret, baseline));
+            caret += scaled.h_advance(id);
+            previous = Some(id);
+
+            let Some(outlined) = self.font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i64 + gx as i64;
+                let py = bounds.min.y as i64 + gy as i64;
+                if px < 0 || py < 0 || px >= cw || py >= ch {
+                    return;
+                }
+                let alpha = (coverage.clamp(0.0, 1.0) as f64 * color[3] as f64).round() as u8;
+                if alpha == 0 {
+                    return;
+                }
+                let fg = Rgba([color[0], color[1], color[2], alpha]);
+                let blended = alpha_blend(canvas.get_pixel(px as u32, py as u32), &fg);
+                canvas.put_pixel(px as u32, py as u32, blended);
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn font() -> Font {
+        Font::bundled()
+    }
+
+    #[test]
+    fn test_load_missing_font_errors() {
+        assert!(Font::load("/nonexistent/font.ttf").is_err());
+    }
+
+    #[test]
+    fn test_system_font_always_loads() {
+        assert!(Font::system().line_height(20.0) > 0.0);
+    }
+
+    #[test]
+    fn test_has_glyphs() {
+        let font = font();
+        assert!(font.has_glyphs("Hello"));
+        assert!(!font.has_glyphs("Hello\u{E000}"));
+    }
+
+    #[test]
+    fn test_text_width_grows_with_text_and_size() {
+        let font = font();
+        assert_eq!(font.text_width("", 16.0), 0.0);
+        assert!(font.text_width("hello world", 16.0) > font.text_width("hello", 16.0));
+        assert!(font.text_width("hello", 32.0) > font.text_width("hello", 16.0));
+        assert!(font.line_height(20.0) > 15.0);
+    }
+
+    #[test]
+    fn test_truncate() {
+        let font = font();
+        let text = "https://example.com/a/very/long/path";
+        assert_eq!(font.truncate("short", 14.0, 1000.0), "short");
+
+        let max = font.text_width(text, 14.0) / 2.0;
+        let truncated = font.truncate(text, 14.0, max);
+        assert!(truncated.ends_with('\u{2026}'));
+        assert!(font.text_width(&truncated, 14.0) <= max);
+    }
+
+    #[test]
+    fn test_draw_text_marks_canvas() {
+        let font = font();
+        let mut canvas = RgbaImage::from_pixel(120, 40, Rgba([255, 255, 255, 255]));
+        font.draw_text(&mut canvas, "Hi", 10.0, 5.0, 24.0, Rgba([0, 0, 0, 255]));
+
+        let dark = canvas.pixels().filter(|p| p[0] < 128).count();
+        assert!(dark > 20);
+        // Nothing drawn left of the origin or past the text
+        let width = font.text_width("Hi", 24.0).ceil() as u32;
+        for y in 0..40 {
+            assert_eq!(canvas.get_pixel(5, y)[0], 255);
+            assert_eq!(canvas.get_pixel(10 + width + 5, y)[0], 255);
+        }
+    }
+
+    #[test]
+    fn test_draw_text_clips_to_canvas() {
+        let font = font();
+        let mut canvas = RgbaImage::new(10, 10);
+        font.draw_text(&mut canvas, "Clipped", -20.0, -5.0, 30.0, Rgba([255, 0, 0, 255]));
+    }
+}
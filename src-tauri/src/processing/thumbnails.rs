@@ -0,0 +1,141 @@
+use std::path::Path;
+
+use image::{DynamicImage, ImageFormat, RgbaImage};
+
+use crate::models::project::Project;
+use crate::processing::decoder::{self, FrameDecoder};
+use crate::processing::redactions::RedactionLayer;
+
+/// Width of the poster thumbnail shown in the project library
+const THUMBNAIL_WIDTH: u32 = 480;
+/// Poster frames come from this far in, since the first moments of a
+/// recording are usually spent switching to the right window
+const POSTER_TIME_MS: u64 = 1000;
+/// Most frames a filmstrip may hold
+const MAX_FILMSTRIP_FRAMES: u32 = 120;
+
+/// Save a redacted poster frame of the recording as a JPEG at `output`
+pub fn generate_thumbnail(project: &Project, output: &Path) -> Result<(), String> {
+    let video_path = &project.video_path;
+    let (source_width, source_height) = decoder::probe_dimensions(video_path)?;
+    let time_ms = POSTER_TIME_MS.min(project.duration_ms / 2);
+    let mut decoder = FrameDecoder::open_scaled(video_path, 1.0, time_ms, THUMBNAIL_WIDTH)?;
+    let redactions = RedactionLayer::scaled(
+        &project.redactions,
+        source_width,
+        source_height,
+        decoder.width(),
+        decoder.height(),
+    )?;
+    let (_, frame) = decoder
+        .next_frame()?
+        .ok_or_else(|| format!("No frame to make a thumbnail from in {}", video_path))?;
+    save_jpeg(&redactions.apply(&frame, time_ms), output)
+}
+
+/// Save `frame_count` redacted frames spread evenly across the recording,
+/// side by side in one JPEG sprite sheet, each `frame_height` pixels tall
+pub fn generate_filmstrip(
+    project: &Project,
+    frame_count: u32,
+    frame_height: u32,
+    output: &Path,
+) -> Result<(), String> {
+    if frame_count == 0 || frame_count > MAX_FILMSTRIP_FRAMES {
+        return Err(format!("A filmstrip needs 1 to {} frames", MAX_FILMSTRIP_FRAMES));
+    }
+    if frame_height == 0 {
+        return Err("Filmstrip frames need a height".to_string());
+    }
+    let video_path = &project.video_path;
+    let (source_width, source_height) = decoder::probe_dimensions(video_path)?;
+    let frame_width = (frame_height as f64 * source_width as f64 / source_height as f64).round().max(1.0) as u32;
+
+    // One decoder at the strip's own frame rate, starting half an interval
+    // in so each frame sits in the middle of the span it stands for
+    let interval_ms = project.duration_ms.max(1) as f64 / frame_count as f64;
+    let fps = 1000.0 / interval_ms;
+    let start_ms = (interval_ms / 2.0) as u64;
+    let mut decoder = FrameDecoder::open_scaled(video_path, fps, start_ms, frame_width)?;
+    let redactions = RedactionLayer::scaled(
+        &project.redactions,
+        source_width,
+        source_height,
+        decoder.width(),
+        decoder.height(),
+    )?;
+    let mut frames = Vec::with_capacity(frame_count as usize);
+    while frames.len() < frame_count as usize {
+        match decoder.next_frame()? {
+            Some((time_ms, frame)) => frames.push(redactions.apply(&frame, start_ms + time_ms).into_owned()),
+            None => break,
+        }
+    }
+    if frames.is_empty() {
+        return Err(format!("No frames to make a filmstrip from in {}", video_path));
+    }
+    save_jpeg(&tile_frames(&frames, frame_count), output)
+}
+
+/// Lay `count` tiles out left to right. When the video ran out early the
+/// last frame fills the remaining tiles.
+fn tile_frames(frames: &[RgbaImage], count: u32) -> RgbaImage {
+    let (width, height) = frames[0].dimensions();
+    let mut sheet = RgbaImage::new(width * count, height);
+    for index in 0..count {
+        let frame = &frames[(index as usize).min(frames.len() - 1)];
+        image::imageops::replace(&mut sheet, frame, (index * width) as i64, 0);
+    }
+    sheet
+}
+
+/// Written to a temporary file and renamed into place, so a cached image
+/// is never seen half written
+fn save_jpeg(image: &RgbaImage, output: &Path) -> Result<(), String> {
+    let file_name = output.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let temp = output.with_file_name(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4()));
+    // JPEG has no alpha channel
+    let saved = DynamicImage::ImageRgba8(image.clone())
+        .to_rgb8()
+        .save_with_format(&temp, ImageFormat::Jpeg)
+        .map_err(|e| format!("Failed to save {}: {}", output.display(), e))
+        .and_then(|()| {
+            std::fs::rename(&temp, output).map_err(|e| format!("Failed to save {}: {}", output.display(), e))
+        });
+    if saved.is_err() {
+        std::fs::remove_file(&temp).ok();
+    }
+    saved
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    #[test]
+    fn test_save_jpeg_leaves_no_temp_file() {
+        let dir = std::env::temp_dir().join(format!("thumbnails-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let output = dir.join("thumbnail.jpg");
+        save_jpeg(&RgbaImage::from_pixel(8, 4, Rgba([255, 0, 0, 255])), &output).unwrap();
+
+        let names: Vec<_> = std::fs::read_dir(&dir).unwrap().flatten().map(|entry| entry.file_name()).collect();
+        assert_eq!(names, ["thumbnail.jpg"]);
+        assert_eq!(image::open(&output).unwrap().width(), 8);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_tile_frames_repeats_last_frame() {
+        let red = RgbaImage::from_pixel(4, 3, Rgba([255, 0, 0, 255]));
+        let blue = RgbaImage::from_pixel(4, 3, Rgba([0, 0, 255, 255]));
+        let sheet = tile_frames(&[red, blue], 3);
+
+        assert_eq!(sheet.dimensions(), (12, 3));
+        assert_eq!(*sheet.get_pixel(1, 1), Rgba([255, 0, 0, 255]));
+        assert_eq!(*sheet.get_pixel(5, 1), Rgba([0, 0, 255, 255]));
+        assert_eq!(*sheet.get_pixel(11, 2), Rgba([0, 0, 255, 255]));
+    }
+}
@@ -0,0 +1,118 @@
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+
+use crate::models::effects::{Watermark, WatermarkAnchor};
+use crate::processing::blend::alpha_blend;
+use crate::processing::layer::{FrameContext, Layer, Surfaces};
+
+/// A logo scaled and faded once, then blended onto every canvas
+pub struct WatermarkLayer {
+    image: RgbaImage,
+    x: u32,
+    y: u32,
+}
+
+impl WatermarkLayer {
+    pub fn new(watermark: &Watermark, canvas_width: u32, canvas_height: u32) -> Result<Self, String> {
+        let logo = image::open(&watermark.path)
+            .map_err(|e| format!("Failed to load watermark image {}: {}", watermark.path, e))?
+            .to_rgba8();
+        Ok(Self::from_image(&logo, watermark, canvas_width, canvas_height))
+    }
+
+    fn from_image(logo: &RgbaImage, watermark: &Watermark, canvas_width: u32, canvas_height: u32) -> Self {
+        let margin = watermark.margin;
+        let max_width = canvas_width.saturating_sub(margin * 2).max(1) as f64;
+        let max_height = canvas_height.saturating_sub(margin * 2).max(1) as f64;
+        let (lw, lh) = (logo.width().max(1) as f64, logo.height().max(1) as f64);
+
+        let target_width = (canvas_width as f64 * watermark.scale.max(0.0)).min(max_width);
+        let scale = (target_width / lw).min(max_height / lh);
+        let width = (lw * scale).round().max(1.0) as u32;
+        let height = (lh * scale).round().max(1.0) as u32;
+
+        let mut image = if (width, height) == logo.dimensions() {
+            logo.clone()
+        } else {
+            imageops::resize(logo, width, height, FilterType::Lanczos3)
+        };
+        let opacity = watermark.opacity.clamp(0.0, 1.0);
+        for pixel in image.pixels_mut() {
+            pixel[3] = (pixel[3] as f64 * opacity).round() as u8;
+        }
+
+        let right = canvas_width.saturating_sub(margin + width);
+        let bottom = canvas_height.saturating_sub(margin + height);
+        let (x, y) = match watermark.anchor {
+            WatermarkAnchor::TopLeft => (margin, margin),
+            WatermarkAnchor::TopRight => (right, margin),
+            WatermarkAnchor::BottomLeft => (margin, bottom),
+            WatermarkAnchor::BottomRight => (right, bottom),
+        };
+
+        Self { image, x, y }
+    }
+
+    /// Blend the watermark onto the finished canvas
+    pub fn draw(&self, canvas: &mut RgbaImage) {
+        for (x, y, pixel) in self.image.enumerate_pixels() {
+            let (px, py) = (self.x + x, self.y + y);
+            if pixel[3] == 0 || px >= canvas.width() || py >= canvas.height() {
+                continue;
+            }
+            let blended = alpha_blend(canvas.get_pixel(px, py), pixel);
+            canvas.put_pixel(px, py, blended);
+        }
+    }
+}
+
+impl Layer for WatermarkLayer {
+    fn render(&self, surfaces: &mut Surfaces, _ctx: &FrameContext) {
+        self.draw(&mut surfaces.canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+    fn watermark(anchor: WatermarkAnchor, opacity: f64) -> Watermark {
+        Watermark { path: String::new(), anchor, margin: 10, scale: 0.2, opacity }
+    }
+
+    #[test]
+    fn test_scaled_and_anchored() {
+        let logo = RgbaImage::from_pixel(100, 50, Rgba([255, 255, 255, 255]));
+        let layer = WatermarkLayer::from_image(&logo, &watermark(WatermarkAnchor::BottomRight, 1.0), 500, 300);
+        assert_eq!(layer.image.dimensions(), (100, 50));
+        assert_eq!((layer.x, layer.y), (390, 240));
+
+        let layer = WatermarkLayer::from_image(&logo, &watermark(WatermarkAnchor::TopLeft, 1.0), 1000, 600);
+        assert_eq!(layer.image.dimensions(), (200, 100));
+        assert_eq!((layer.x, layer.y), (10, 10));
+    }
+
+    #[test]
+    fn test_draw_applies_opacity() {
+        let logo = RgbaImage::from_pixel(20, 20, Rgba([255, 255, 255, 255]));
+        let layer = WatermarkLayer::from_image(&logo, &watermark(WatermarkAnchor::TopLeft, 0.5), 100, 100);
+        let mut canvas = RgbaImage::from_pixel(100, 100, BLACK);
+        layer.draw(&mut canvas);
+
+        let value = canvas.get_pixel(15, 15)[0];
+        assert!((185..=190).contains(&value), "got {}", value);
+        assert_eq!(*canvas.get_pixel(5, 5), BLACK);
+        assert_eq!(*canvas.get_pixel(50, 50), BLACK);
+    }
+
+    #[test]
+    fn test_missing_image_errors() {
+        let mut missing = watermark(WatermarkAnchor::TopLeft, 1.0);
+        missing.path = "/nonexistent/logo.png".to_string();
+        assert!(WatermarkLayer::new(&missing, 100, 100).is_err());
+    }
+}
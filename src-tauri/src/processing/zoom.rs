@@ -1,5 +1,9 @@
+use image::RgbaImage;
+
 use crate::models::effects::{EasingType, ZoomConfig};
 use crate::models::events::MouseEvent;
+use crate::processing::layer::{ExportGeometry, FrameContext, Layer, Surfaces};
+use crate::processing::resample;
 
 /// Represents a zoom animation triggered by a click event
 #[derive(Debug, Clone)]
@@ -19,6 +23,16 @@ pub struct ZoomKeyframe {
     pub hold_ms: u64,
     pub zoom_out_ms: u64,
     pub easing: EasingType,
+    /// Later clicks in the same segment, panned to in order while zoomed
+    pub pans: Vec<ZoomPan>,
+}
+
+/// A move of the zoom center to a later click, taking as long as zooming in
+#[derive(Debug, Clone)]
+pub struct ZoomPan {
+    pub start_ms: u64,
+    pub center_x: f64,
+    pub center_y: f64,
 }
 
 /// The computed viewport for a single frame
@@ -36,10 +50,15 @@ pub struct FrameViewport {
     pub center_y: f64,
 }
 
-/// Generates zoom keyframes from click events
+/// Generates zoom keyframes from click events.
+///
+/// Clicks close together in time and space are grouped into one segment
+/// that zooms in on the first click, pans to each later one and zooms out
+/// after the last, instead of zooming out and back in between them.
 pub fn generate_zoom_keyframes(
     click_events: &[&MouseEvent],
     config: &ZoomConfig,
+    source_width: f64,
 ) -> Vec<ZoomKeyframe> {
     if !config.enabled {
         return Vec::new();
@@ -47,63 +66,112 @@ pub fn generate_zoom_keyframes(
 
     let total_duration = config.zoom_in_duration_ms + config.hold_duration_ms + config.zoom_out_duration_ms;
 
-    click_events
-        .iter()
-        .map(|event| {
+    cluster_clicks(click_events, config, source_width)
+        .into_iter()
+        .map(|cluster| {
+            let first = cluster[0];
+            let last = cluster[cluster.len() - 1];
             ZoomKeyframe {
-                start_ms: event.timestamp_ms,
-                end_ms: event.timestamp_ms + total_duration,
-                center_x: event.x,
-                center_y: event.y,
+                start_ms: first.timestamp_ms,
+                end_ms: last.timestamp_ms + total_duration,
+                center_x: first.x,
+                center_y: first.y,
                 peak_zoom: config.zoom_level,
                 zoom_in_ms: config.zoom_in_duration_ms,
-                hold_ms: config.hold_duration_ms,
+                // Stay zoomed until the last pan has settled and been held
+                hold_ms: last.timestamp_ms - first.timestamp_ms + config.hold_duration_ms,
                 zoom_out_ms: config.zoom_out_duration_ms,
                 easing: config.easing.clone(),
+                pans: cluster[1..]
+                    .iter()
+                    .map(|event| ZoomPan {
+                        start_ms: event.timestamp_ms,
+                        center_x: event.x,
+                        center_y: event.y,
+                    })
+                    .collect(),
             }
         })
         .collect()
 }
 
+/// Split time-ordered clicks into runs where each click follows the
+/// previous one within `cluster_gap_ms` and `cluster_distance`
+fn cluster_clicks<'a>(
+    click_events: &[&'a MouseEvent],
+    config: &ZoomConfig,
+    source_width: f64,
+) -> Vec<Vec<&'a MouseEvent>> {
+    let max_distance = config.cluster_distance.max(0.0) * source_width;
+    let mut clusters: Vec<Vec<&MouseEvent>> = Vec::new();
+
+    for &event in click_events {
+        let joins = clusters.last().and_then(|cluster| cluster.last()).is_some_and(|previous| {
+            let gap = event.timestamp_ms.saturating_sub(previous.timestamp_ms);
+            let distance = (event.x - previous.x).hypot(event.y - previous.y);
+            config.cluster_gap_ms > 0 && gap <= config.cluster_gap_ms && distance <= max_distance
+        });
+        match clusters.last_mut() {
+            Some(cluster) if joins => cluster.push(event),
+            _ => clusters.push(vec![event]),
+        }
+    }
+    clusters
+}
+
+/// Most sub-frames blended for a single motion-blurred frame
+const MAX_MOTION_BLUR_SAMPLES: usize = 16;
+
 /// Calculate the zoom factor at a given time based on active keyframes
 pub fn compute_zoom_at_time(
     time_ms: u64,
     keyframes: &[ZoomKeyframe],
     source_width: f64,
     source_height: f64,
+) -> FrameViewport {
+    compute_zoom_at(time_ms as f64, keyframes, source_width, source_height)
+}
+
+/// Same as `compute_zoom_at_time`, for timestamps between whole milliseconds
+pub fn compute_zoom_at(
+    time_ms: f64,
+    keyframes: &[ZoomKeyframe],
+    source_width: f64,
+    source_height: f64,
 ) -> FrameViewport {
     let mut max_zoom: f64 = 1.0;
     let mut center_x = source_width / 2.0;
     let mut center_y = source_height / 2.0;
 
     for kf in keyframes {
-        if time_ms < kf.start_ms || time_ms > kf.end_ms {
+        if time_ms < kf.start_ms as f64 || time_ms > kf.end_ms as f64 {
             continue;
         }
 
-        let elapsed = time_ms - kf.start_ms;
+        let elapsed = time_ms - kf.start_ms as f64;
+        let zoom_in_ms = kf.zoom_in_ms as f64;
+        let hold_ms = kf.hold_ms as f64;
         let zoom_factor;
 
-        if elapsed < kf.zoom_in_ms {
+        if elapsed < zoom_in_ms {
             // Zooming in phase
-            let t = elapsed as f64 / kf.zoom_in_ms as f64;
+            let t = elapsed / zoom_in_ms;
             let eased_t = apply_easing(t, &kf.easing);
             zoom_factor = 1.0 + (kf.peak_zoom - 1.0) * eased_t;
-        } else if elapsed < kf.zoom_in_ms + kf.hold_ms {
+        } else if elapsed < zoom_in_ms + hold_ms {
             // Hold phase
             zoom_factor = kf.peak_zoom;
         } else {
             // Zooming out phase
-            let out_elapsed = elapsed - kf.zoom_in_ms - kf.hold_ms;
-            let t = out_elapsed as f64 / kf.zoom_out_ms as f64;
+            let out_elapsed = elapsed - zoom_in_ms - hold_ms;
+            let t = out_elapsed / kf.zoom_out_ms as f64;
             let eased_t = apply_easing(t, &kf.easing);
             zoom_factor = kf.peak_zoom - (kf.peak_zoom - 1.0) * eased_t;
         }
 
         if zoom_factor > max_zoom {
             max_zoom = zoom_factor;
-            center_x = kf.center_x;
-            center_y = kf.center_y;
+            (center_x, center_y) = center_at(kf, time_ms);
         }
     }
 
@@ -128,8 +196,166 @@ pub fn compute_zoom_at_time(
     }
 }
 
+/// Zoom center of `kf` at `time_ms`, following its pans. A pan that starts
+/// before the previous one settles sets off from wherever the center is.
+fn center_at(kf: &ZoomKeyframe, time_ms: f64) -> (f64, f64) {
+    let (mut x, mut y) = (kf.center_x, kf.center_y);
+    for pan in &kf.pans {
+        let elapsed = time_ms - pan.start_ms as f64;
+        if elapsed <= 0.0 {
+            break;
+        }
+        let t = if kf.zoom_in_ms > 0 { elapsed / kf.zoom_in_ms as f64 } else { 1.0 };
+        let eased_t = apply_easing(t, &kf.easing);
+        x += (pan.center_x - x) * eased_t;
+        y += (pan.center_y - y) * eased_t;
+    }
+    (x, y)
+}
+
+/// Viewports to blend for a motion-blurred frame at `time_ms`.
+///
+/// The shutter is centered on the frame timestamp and stays open for
+/// `shutter_angle / 360` of the frame interval. Returns a single viewport
+/// when the zoom barely moves during that window, so static frames cost
+/// nothing extra; otherwise the sample count grows with the on-screen
+/// movement (up to `MAX_MOTION_BLUR_SAMPLES`).
+pub fn motion_blur_viewports(
+    time_ms: u64,
+    frame_interval_ms: f64,
+    shutter_angle: f64,
+    keyframes: &[ZoomKeyframe],
+    source_width: f64,
+    source_height: f64,
+) -> Vec<FrameViewport> {
+    let center = compute_zoom_at_time(time_ms, keyframes, source_width, source_height);
+    let shutter_ms = frame_interval_ms * shutter_angle.clamp(0.0, 360.0) / 360.0;
+    if shutter_ms <= 0.0 {
+        return vec![center];
+    }
+
+    let open = time_ms as f64 - shutter_ms / 2.0;
+    let close = time_ms as f64 + shutter_ms / 2.0;
+
+    // Movement in output pixels: a source pixel covers `zoom` output pixels
+    let probes: Vec<FrameViewport> = shutter_probe_times(open, close, keyframes)
+        .map(|t| compute_zoom_at(t, keyframes, source_width, source_height))
+        .collect();
+    let zoom = probes.iter().fold(center.zoom, |max, v| max.max(v.zoom));
+    let spread = |value: fn(&FrameViewport) -> f64| {
+        let (lo, hi) = probes
+            .iter()
+            .map(value)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+        hi - lo
+    };
+    let movement = spread(|v| v.x)
+        .max(spread(|v| v.y))
+        .max(spread(|v| v.width))
+        .max(spread(|v| v.height))
+        * zoom;
+    if movement < 0.5 {
+        return vec![center];
+    }
+
+    let samples = (movement.ceil() as usize).clamp(2, MAX_MOTION_BLUR_SAMPLES);
+    (0..samples)
+        .map(|i| {
+            let t = open + shutter_ms * (i as f64 + 0.5) / samples as f64;
+            compute_zoom_at(t, keyframes, source_width, source_height)
+        })
+        .collect()
+}
+
+/// Times across the shutter to look for movement at: evenly spaced, plus
+/// every keyframe phase change inside the window, so a zoom or pan that
+/// starts and settles between the shutter's ends isn't mistaken for static
+fn shutter_probe_times(open: f64, close: f64, keyframes: &[ZoomKeyframe]) -> impl Iterator<Item = f64> + '_ {
+    let even = (0..=MAX_MOTION_BLUR_SAMPLES)
+        .map(move |i| open + (close - open) * i as f64 / MAX_MOTION_BLUR_SAMPLES as f64);
+    let phases = keyframes
+        .iter()
+        .flat_map(|kf| {
+            let zoomed_in = kf.start_ms + kf.zoom_in_ms;
+            let pans = kf.pans.iter().flat_map(move |pan| [pan.start_ms, pan.start_ms + kf.zoom_in_ms]);
+            [kf.start_ms, zoomed_in, zoomed_in + kf.hold_ms, kf.end_ms].into_iter().chain(pans)
+        })
+        .map(|t| t as f64)
+        .filter(move |&t| t > open && t < close);
+    even.chain(phases)
+}
+
+/// Resamples the zoomed region of the source into the video surface,
+/// blending sub-frames while the zoom moves if motion blur is enabled
+pub struct ZoomLayer {
+    keyframes: Vec<ZoomKeyframe>,
+    motion_blur: bool,
+    shutter_angle: f64,
+    width: u32,
+    height: u32,
+    /// Time between output frames, used to size the motion blur shutter
+    frame_interval_ms: f64,
+}
+
+impl ZoomLayer {
+    pub fn new(keyframes: Vec<ZoomKeyframe>, config: &ZoomConfig) -> Self {
+        Self {
+            keyframes,
+            motion_blur: config.motion_blur,
+            shutter_angle: config.shutter_angle,
+            width: 0,
+            height: 0,
+            frame_interval_ms: 0.0,
+        }
+    }
+
+    fn sample(&self, source_frame: &RgbaImage, time_ms: u64, viewport: &FrameViewport) -> RgbaImage {
+        let (width, height) = (self.width, self.height);
+        if !self.motion_blur {
+            return resample::sample_viewport(source_frame, viewport, width, height);
+        }
+
+        let viewports = motion_blur_viewports(
+            time_ms,
+            self.frame_interval_ms,
+            self.shutter_angle,
+            &self.keyframes,
+            width as f64,
+            height as f64,
+        );
+        if viewports.len() < 2 {
+            return resample::sample_viewport(source_frame, viewport, width, height);
+        }
+
+        let mut sum = vec![0u32; width as usize * height as usize * 4];
+        for sub_viewport in &viewports {
+            let sample = resample::sample_viewport(source_frame, sub_viewport, width, height);
+            for (acc, &value) in sum.iter_mut().zip(sample.as_raw()) {
+                *acc += value as u32;
+            }
+        }
+
+        let count = viewports.len() as u32;
+        let averaged = sum.into_iter().map(|v| ((v + count / 2) / count) as u8).collect();
+        RgbaImage::from_raw(width, height, averaged).expect("buffer matches frame size")
+    }
+}
+
+impl Layer for ZoomLayer {
+    fn prepare(&mut self, geometry: &ExportGeometry) -> Result<(), String> {
+        self.width = geometry.source_width;
+        self.height = geometry.source_height;
+        self.frame_interval_ms = geometry.frame_interval_ms;
+        Ok(())
+    }
+
+    fn render(&self, surfaces: &mut Surfaces, ctx: &FrameContext) {
+        surfaces.video = self.sample(&surfaces.source, ctx.time_ms, &ctx.viewport);
+    }
+}
+
 /// Apply easing function to a normalized time value (0.0 - 1.0)
-fn apply_easing(t: f64, easing: &EasingType) -> f64 {
+pub fn apply_easing(t: f64, easing: &EasingType) -> f64 {
     let t = t.clamp(0.0, 1.0);
     match easing {
         EasingType::Linear => t,
@@ -184,6 +410,7 @@ mod tests {
             hold_ms: 500,
             zoom_out_ms: 300,
             easing: EasingType::Linear,
+            pans: Vec::new(),
         };
 
         // At the hold phase (400ms in)
@@ -237,6 +464,7 @@ mod tests {
             hold_ms: 500,
             zoom_out_ms: 300,
             easing: EasingType::Linear,
+            pans: Vec::new(),
         };
 
         // 150ms into a 300ms zoom-in with linear easing = 50% progress = zoom 1.5
@@ -256,6 +484,7 @@ mod tests {
             hold_ms: 500,
             zoom_out_ms: 300,
             easing: EasingType::Linear,
+            pans: Vec::new(),
         };
 
         // 150ms into the 300ms zoom-out phase (at 950ms total)
@@ -275,6 +504,7 @@ mod tests {
             hold_ms: 500,
             zoom_out_ms: 300,
             easing: EasingType::Linear,
+            pans: Vec::new(),
         };
 
         let viewport = compute_zoom_at_time(500, &[kf.clone()], 1920.0, 1080.0);
@@ -296,6 +526,7 @@ mod tests {
             hold_ms: 500,
             zoom_out_ms: 300,
             easing: EasingType::Linear,
+            pans: Vec::new(),
         };
 
         let viewport = compute_zoom_at_time(400, &[kf], 1920.0, 1080.0);
@@ -308,7 +539,7 @@ mod tests {
     #[test]
     fn test_generate_zoom_keyframes_empty_events() {
         let config = ZoomConfig::default();
-        let result = generate_zoom_keyframes(&[], &config);
+        let result = generate_zoom_keyframes(&[], &config, 1920.0);
         assert!(result.is_empty());
     }
 
@@ -325,7 +556,176 @@ mod tests {
             event_type: crate::models::events::EventType::Click,
             button: crate::models::events::MouseButton::Left,
         };
-        let result = generate_zoom_keyframes(&[&event], &config);
+        let result = generate_zoom_keyframes(&[&event], &config, 1920.0);
         assert!(result.is_empty());
     }
+
+    fn click(timestamp_ms: u64, x: f64, y: f64) -> MouseEvent {
+        MouseEvent {
+            timestamp_ms,
+            x,
+            y,
+            event_type: crate::models::events::EventType::Click,
+            button: crate::models::events::MouseButton::Left,
+        }
+    }
+
+    #[test]
+    fn test_nearby_clicks_share_one_segment() {
+        let clicks = [click(100, 500.0, 300.0), click(600, 600.0, 350.0), click(1200, 700.0, 300.0)];
+        let refs: Vec<&MouseEvent> = clicks.iter().collect();
+        let keyframes = generate_zoom_keyframes(&refs, &ZoomConfig::default(), 1920.0);
+
+        assert_eq!(keyframes.len(), 1);
+        let kf = &keyframes[0];
+        assert_eq!((kf.start_ms, kf.end_ms), (100, 2300));
+        assert_eq!(kf.hold_ms, 1600);
+        assert_eq!(kf.pans.len(), 2);
+        assert_eq!(kf.pans[1].start_ms, 1200);
+    }
+
+    #[test]
+    fn test_distant_or_late_clicks_split() {
+        let clicks = [click(100, 100.0, 100.0), click(400, 1800.0, 1000.0), click(4000, 1800.0, 1000.0)];
+        let refs: Vec<&MouseEvent> = clicks.iter().collect();
+        assert_eq!(generate_zoom_keyframes(&refs, &ZoomConfig::default(), 1920.0).len(), 3);
+
+        // A zero gap turns clustering off
+        let nearby = [click(100, 500.0, 300.0), click(200, 510.0, 300.0)];
+        let refs: Vec<&MouseEvent> = nearby.iter().collect();
+        let config = ZoomConfig { cluster_gap_ms: 0, ..ZoomConfig::default() };
+        assert_eq!(generate_zoom_keyframes(&refs, &config, 1920.0).len(), 2);
+    }
+
+    #[test]
+    fn test_segment_pans_between_clicks() {
+        let clicks = [click(0, 600.0, 400.0), click(1000, 900.0, 400.0)];
+        let refs: Vec<&MouseEvent> = clicks.iter().collect();
+        let config = ZoomConfig { easing: EasingType::Linear, ..ZoomConfig::default() };
+        let keyframes = generate_zoom_keyframes(&refs, &config, 1920.0);
+
+        // Fully zoomed throughout, moving from the first click to the second
+        let before = compute_zoom_at_time(900, &keyframes, 1920.0, 1080.0);
+        let mid_pan = compute_zoom_at_time(1150, &keyframes, 1920.0, 1080.0);
+        let after = compute_zoom_at_time(1400, &keyframes, 1920.0, 1080.0);
+        for viewport in [&before, &mid_pan, &after] {
+            assert!((viewport.zoom - 2.0).abs() < 1e-10);
+        }
+        assert!((before.center_x - 600.0).abs() < 1e-10);
+        assert!((mid_pan.center_x - 750.0).abs() < 1e-10);
+        assert!((after.center_x - 900.0).abs() < 1e-10);
+    }
+
+    fn linear_keyframe() -> ZoomKeyframe {
+        ZoomKeyframe {
+            start_ms: 0,
+            end_ms: 1100,
+            center_x: 960.0,
+            center_y: 540.0,
+            peak_zoom: 2.0,
+            zoom_in_ms: 300,
+            hold_ms: 500,
+            zoom_out_ms: 300,
+            easing: EasingType::Linear,
+            pans: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_compute_zoom_at_fractional_time() {
+        let kf = linear_keyframe();
+        let a = compute_zoom_at(150.0, std::slice::from_ref(&kf), 1920.0, 1080.0);
+        let b = compute_zoom_at(150.5, std::slice::from_ref(&kf), 1920.0, 1080.0);
+        let c = compute_zoom_at_time(151, &[kf], 1920.0, 1080.0);
+        assert!(a.zoom < b.zoom && b.zoom < c.zoom);
+    }
+
+    #[test]
+    fn test_motion_blur_skipped_when_static() {
+        // No keyframes, and the middle of the hold phase
+        assert_eq!(motion_blur_viewports(500, 33.3, 180.0, &[], 1920.0, 1080.0).len(), 1);
+        assert_eq!(
+            motion_blur_viewports(500, 33.3, 180.0, &[linear_keyframe()], 1920.0, 1080.0).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_motion_blur_catches_movement_inside_shutter() {
+        // A quick zoom that starts and ends between the shutter's ends
+        let kf = ZoomKeyframe {
+            start_ms: 1000,
+            end_ms: 1010,
+            zoom_in_ms: 5,
+            hold_ms: 0,
+            zoom_out_ms: 5,
+            ..linear_keyframe()
+        };
+        let viewports = motion_blur_viewports(1005, 40.0, 180.0, &[kf], 1920.0, 1080.0);
+        assert!(viewports.len() > 1);
+        assert!(viewports.iter().any(|v| v.zoom > 1.0));
+    }
+
+    #[test]
+    fn test_motion_blur_zero_shutter() {
+        let viewports = motion_blur_viewports(150, 33.3, 0.0, &[linear_keyframe()], 1920.0, 1080.0);
+        assert_eq!(viewports.len(), 1);
+    }
+
+    #[test]
+    fn test_motion_blur_samples_span_shutter() {
+        let kf = linear_keyframe();
+        let viewports = motion_blur_viewports(150, 40.0, 180.0, std::slice::from_ref(&kf), 1920.0, 1080.0);
+        assert!(viewports.len() >= 2 && viewports.len() <= MAX_MOTION_BLUR_SAMPLES);
+
+        // Samples are ordered in time and straddle the frame timestamp
+        let zooms: Vec<f64> = viewports.iter().map(|v| v.zoom).collect();
+        assert!(zooms.windows(2).all(|w| w[0] < w[1]));
+        let center = compute_zoom_at_time(150, std::slice::from_ref(&kf), 1920.0, 1080.0).zoom;
+        assert!(zooms[0] < center && center < zooms[zooms.len() - 1]);
+
+        // Everything stays within the 10ms window around the frame
+        assert!(zooms[0] > compute_zoom_at(140.0, std::slice::from_ref(&kf), 1920.0, 1080.0).zoom);
+        assert!(zooms[zooms.len() - 1] < compute_zoom_at(160.0, &[kf], 1920.0, 1080.0).zoom);
+    }
+
+    #[test]
+    fn test_motion_blur_wider_shutter_takes_more_samples() {
+        // A slow zoom so neither shutter hits the sample cap
+        let kf = ZoomKeyframe {
+            end_ms: 3800,
+            zoom_in_ms: 3000,
+            ..linear_keyframe()
+        };
+        let narrow = motion_blur_viewports(150, 33.3, 45.0, std::slice::from_ref(&kf), 1920.0, 1080.0);
+        let wide = motion_blur_viewports(150, 33.3, 360.0, &[kf], 1920.0, 1080.0);
+        assert!(wide.len() > narrow.len());
+    }
+
+    #[test]
+    fn test_zoom_layer_fills_video_surface() {
+        use image::Rgba;
+
+        let red = Rgba([255, 0, 0, 255]);
+        let source = RgbaImage::from_fn(40, 20, |x, _| if x < 20 { red } else { Rgba([0, 0, 255, 255]) });
+        let config = ZoomConfig { motion_blur: false, ..ZoomConfig::default() };
+        let mut layer = ZoomLayer::new(Vec::new(), &config);
+        let geometry = ExportGeometry {
+            source_width: 40,
+            source_height: 20,
+            canvas_width: 40,
+            canvas_height: 20,
+            frame_interval_ms: 33.3,
+        };
+        layer.prepare(&geometry).unwrap();
+
+        // The left half of the source, zoomed to fill the frame
+        let viewport = FrameViewport { x: 0.0, y: 0.0, width: 20.0, height: 10.0, zoom: 2.0, center_x: 10.0, center_y: 5.0 };
+        let ctx = FrameContext { time_ms: 0, viewport, overlay_frame: None };
+        let mut surfaces = Surfaces::new(&source);
+        layer.render(&mut surfaces, &ctx);
+
+        assert_eq!(surfaces.video.dimensions(), (40, 20));
+        assert_eq!(*surfaces.video.get_pixel(36, 10), red);
+    }
 }
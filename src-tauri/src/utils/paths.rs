@@ -1,6 +1,9 @@
 use std::fs;
 use std::path::PathBuf;
 
+use crate::models::project::Project;
+use crate::models::redactions::Redaction;
+
 pub fn app_data_dir() -> PathBuf {
     let dir = dirs::data_dir()
         .unwrap_or_else(|| PathBuf::from("."))
@@ -9,6 +12,10 @@ pub fn app_data_dir() -> PathBuf {
     dir
 }
 
+pub fn settings_path() -> PathBuf {
+    app_data_dir().join("settings.json")
+}
+
 pub fn recordings_dir() -> PathBuf {
     let dir = app_data_dir().join("recordings");
     fs::create_dir_all(&dir).ok();
@@ -47,6 +54,61 @@ pub fn project_metadata_path(project_id: &str) -> PathBuf {
     project_dir(project_id).join("project.json")
 }
 
+const THUMBNAIL_PREFIX: &str = "thumbnail";
+const FILMSTRIP_PREFIX: &str = "filmstrip-";
+
+pub fn project_thumbnail_path(project: &Project) -> PathBuf {
+    project_dir(&project.id).join(format!(
+        "{}{}.jpg",
+        THUMBNAIL_PREFIX,
+        redactions_suffix(&project.redactions)
+    ))
+}
+
+/// Filmstrips are cached per layout, since the timeline asks for several
+pub fn project_filmstrip_path(project: &Project, frame_count: u32, frame_height: u32) -> PathBuf {
+    project_dir(&project.id).join(format!(
+        "{}{}x{}{}.jpg",
+        FILMSTRIP_PREFIX,
+        frame_count,
+        frame_height,
+        redactions_suffix(&project.redactions)
+    ))
+}
+
+/// Every cached thumbnail and filmstrip of the project, whatever its
+/// layout or redactions
+pub fn project_preview_paths(project_id: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(project_dir(project_id)) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name().is_some_and(|name| {
+                let name = name.to_string_lossy();
+                (name.starts_with(THUMBNAIL_PREFIX) || name.starts_with(FILMSTRIP_PREFIX)) && name.ends_with(".jpg")
+            })
+        })
+        .collect()
+}
+
+/// Cached previews are redacted, so their names carry a hash of the
+/// redactions they were made with and go unused once those change.
+/// Unredacted previews keep plain names.
+fn redactions_suffix(redactions: &[Redaction]) -> String {
+    if redactions.is_empty() {
+        return String::new();
+    }
+    let json = serde_json::to_vec(redactions).unwrap_or_default();
+    // FNV-1a, which unlike `DefaultHasher` stays the same across Rust releases
+    let hash = json
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3));
+    format!("-{:016x}", hash)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,4 +136,49 @@ mod tests {
         assert!(path_str.contains("abc-123"), "Path should contain project ID: {path_str}");
         assert!(path_str.ends_with("project.json"));
     }
+
+    fn project() -> Project {
+        Project::new(
+            "abc-123".to_string(),
+            "Test".to_string(),
+            "".to_string(),
+            "".to_string(),
+            100,
+            100,
+            30.0,
+        )
+    }
+
+    #[test]
+    fn test_project_filmstrip_path_contains_layout() {
+        let path = project_filmstrip_path(&project(), 24, 60);
+        let path_str = path.to_string_lossy();
+        assert!(path_str.contains("abc-123"), "Path should contain project ID: {path_str}");
+        assert!(path_str.ends_with("filmstrip-24x60.jpg"));
+    }
+
+    #[test]
+    fn test_preview_paths_change_with_redactions() {
+        let mut project = project();
+        assert!(project_thumbnail_path(&project).ends_with("thumbnail.jpg"));
+
+        project.redactions.push(Redaction {
+            id: "r1".to_string(),
+            start_ms: 0,
+            end_ms: 1000,
+            x: 10.0,
+            y: 10.0,
+            width: 50.0,
+            height: 20.0,
+            mode: Default::default(),
+        });
+        let thumbnail = project_thumbnail_path(&project);
+        let filmstrip = project_filmstrip_path(&project, 24, 60);
+        assert!(!thumbnail.ends_with("thumbnail.jpg"));
+        assert_eq!(thumbnail, project_thumbnail_path(&project));
+
+        project.redactions[0].x = 11.0;
+        assert_ne!(project_thumbnail_path(&project), thumbnail);
+        assert_ne!(project_filmstrip_path(&project, 24, 60), filmstrip);
+    }
 }
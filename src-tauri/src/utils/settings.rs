@@ -0,0 +1,23 @@
+use crate::models::settings::AppSettings;
+use crate::utils::paths;
+
+/// Read the app settings, or the defaults when none have been saved yet
+pub fn load() -> Result<AppSettings, String> {
+    let path = paths::settings_path();
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read settings: {}", e))?;
+    let settings: AppSettings = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse settings: {}", e))?;
+    Ok(settings)
+}
+
+pub fn save(settings: &AppSettings) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize: {}", e))?;
+    std::fs::write(paths::settings_path(), json)
+        .map_err(|e| format!("Failed to write settings: {}", e))?;
+    Ok(())
+}
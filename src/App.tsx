@@ -10,9 +10,16 @@ import { useRecording } from "./hooks/useRecording";
 import { useProject } from "./hooks/useProject";
 import { SourceSelector } from "./components/recording/SourceSelector";
 import { RecordingControls } from "./components/recording/RecordingControls";
+import { SelectField } from "./components/ui/SelectField";
 import { EditorView } from "./components/editor/EditorView";
 import * as api from "./lib/tauri";
-import type { Project } from "./lib/tauri";
+import type { AppSettings, KeyCapture, Project } from "./lib/tauri";
+
+const KEY_CAPTURE_OPTIONS = [
+  { value: "Off", label: "Off" },
+  { value: "ShortcutsOnly", label: "Shortcuts only" },
+  { value: "All", label: "All keys, including typed text" },
+];
 
 function App() {
   const { view } = useRecordingStore();
@@ -39,13 +46,27 @@ function HomeView() {
   const [projects, setProjects] = useState<Project[]>([]);
   const [hasFfmpeg, setHasFfmpeg] = useState<boolean | null>(null);
   const [error, setError] = useState<string | null>(null);
+  const [settings, setSettings] = useState<AppSettings | null>(null);
 
-  // Load projects and check FFmpeg on mount
+  // Load projects, settings and check FFmpeg on mount
   useEffect(() => {
     api.listProjects().then(setProjects).catch(console.error);
+    api.getSettings().then(setSettings).catch(console.error);
     api.checkFfmpeg().then(setHasFfmpeg).catch(() => setHasFfmpeg(false));
   }, []);
 
+  // Keystrokes are only recorded once the user opts in here
+  const handleKeyCaptureChange = async (value: string) => {
+    if (!settings) return;
+    const updated = { ...settings, key_capture: value as KeyCapture };
+    try {
+      await api.saveSettings(updated);
+      setSettings(updated);
+    } catch (err) {
+      setError(String(err));
+    }
+  };
+
   // Refresh projects when returning from editor
   useEffect(() => {
     api.listProjects().then(setProjects).catch(console.error);
@@ -128,6 +149,18 @@ function HomeView() {
           />
         )}
 
+        {/* Keystroke capture */}
+        {!isRecording && settings && (
+          <div className="w-64">
+            <SelectField
+              label="Record keystrokes"
+              value={settings.key_capture ?? "Off"}
+              options={KEY_CAPTURE_OPTIONS}
+              onChange={handleKeyCaptureChange}
+            />
+          </div>
+        )}
+
         {/* Recording controls */}
         <RecordingControls
           isRecording={isRecording}
@@ -4,7 +4,7 @@ import { useEditorStore } from "../../stores/editorStore";
 import { SliderField } from "../ui/SliderField";
 import { SelectField } from "../ui/SelectField";
 import { GRADIENT_PRESETS } from "../../lib/constants";
-import type { EasingType, Background } from "../../lib/tauri";
+import type { EasingType, Background, ExportFormat } from "../../lib/tauri";
 
 export function EffectsPanel() {
   return (
@@ -138,6 +138,7 @@ function BackgroundTab() {
         <div className="grid grid-cols-4 gap-2">
           {GRADIENT_PRESETS.map((preset) => {
             const isSelected =
+              typeof frameStyle.background === "object" &&
               "Gradient" in frameStyle.background &&
               frameStyle.background.Gradient.colors[0] === preset.colors[0];
             return (
@@ -240,8 +241,11 @@ function ExportTab() {
         options={[
           { value: "Mp4", label: "MP4 (H.264)" },
           { value: "Gif", label: "GIF" },
+          { value: "WebM", label: "WebM (VP9, transparent)" },
+          { value: "ProRes", label: "MOV (ProRes 4444, transparent)" },
+          { value: "Apng", label: "APNG (transparent)" },
         ]}
-        onChange={(v) => updateExportConfig({ format: v as "Mp4" | "Gif" })}
+        onChange={(v) => updateExportConfig({ format: v as ExportFormat })}
       />
 
       <SelectField
@@ -5,6 +5,15 @@ import { Button } from "../ui/Button";
 import { useExport } from "../../hooks/useExport";
 import { useRecordingStore } from "../../stores/recordingStore";
 import { useEditorStore } from "../../stores/editorStore";
+import type { ExportFormat } from "../../lib/tauri";
+
+const EXTENSIONS: Record<ExportFormat, string> = {
+  Mp4: "mp4",
+  Gif: "gif",
+  WebM: "webm",
+  ProRes: "mov",
+  Apng: "png",
+};
 
 export function ExportDialog() {
   const [open, setOpen] = useState(false);
@@ -20,7 +29,7 @@ export function ExportDialog() {
 
     // Set output path if not set
     if (!exportConfig.output_path && currentProject) {
-      const ext = exportConfig.format === "Gif" ? "gif" : "mp4";
+      const ext = EXTENSIONS[exportConfig.format];
       const defaultPath = currentProject.video_path.replace(
         /recording\.mp4$/,
         `export.${ext}`
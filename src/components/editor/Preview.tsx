@@ -23,16 +23,35 @@ export function Preview() {
     const h = canvas.height;
 
     // Draw background
-    if ("Gradient" in bg) {
-      const { colors, angle } = bg.Gradient;
+    if (bg === "Transparent") {
+      ctx.clearRect(0, 0, w, h);
+      ctx.fillStyle = "transparent";
+    } else if ("Gradient" in bg) {
+      const { colors, angle, kind = "Linear", stops } = bg.Gradient;
       const rad = (angle * Math.PI) / 180;
-      const x1 = w / 2 - (Math.cos(rad) * w) / 2;
-      const y1 = h / 2 - (Math.sin(rad) * h) / 2;
-      const x2 = w / 2 + (Math.cos(rad) * w) / 2;
-      const y2 = h / 2 + (Math.sin(rad) * h) / 2;
-      const gradient = ctx.createLinearGradient(x1, y1, x2, y2);
+      const cx = (bg.Gradient.center_x ?? 0.5) * w;
+      const cy = (bg.Gradient.center_y ?? 0.5) * h;
+      let gradient: CanvasGradient;
+      if (kind === "Radial") {
+        const radius = Math.max(
+          Math.hypot(cx, cy),
+          Math.hypot(w - cx, cy),
+          Math.hypot(cx, h - cy),
+          Math.hypot(w - cx, h - cy)
+        );
+        gradient = ctx.createRadialGradient(cx, cy, 0, cx, cy, radius);
+      } else if (kind === "Conic") {
+        gradient = ctx.createConicGradient(rad, cx, cy);
+      } else {
+        const x1 = w / 2 - (Math.cos(rad) * w) / 2;
+        const y1 = h / 2 - (Math.sin(rad) * h) / 2;
+        const x2 = w / 2 + (Math.cos(rad) * w) / 2;
+        const y2 = h / 2 + (Math.sin(rad) * h) / 2;
+        gradient = ctx.createLinearGradient(x1, y1, x2, y2);
+      }
       colors.forEach((color, i) => {
-        gradient.addColorStop(i / Math.max(1, colors.length - 1), color);
+        const offset = stops?.[i] ?? i / Math.max(1, colors.length - 1);
+        gradient.addColorStop(offset, color);
       });
       ctx.fillStyle = gradient;
     } else if ("Solid" in bg) {
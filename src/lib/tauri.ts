@@ -26,8 +26,22 @@ export interface MouseEvent {
   button: "Left" | "Right" | "Middle" | "Other";
 }
 
+export interface Modifiers {
+  command?: boolean;
+  shift?: boolean;
+  option?: boolean;
+  control?: boolean;
+}
+
+export interface KeyEvent {
+  timestamp_ms: number;
+  key: string;
+  modifiers?: Modifiers;
+}
+
 export interface RecordedEvents {
   mouse_events: MouseEvent[];
+  key_events?: KeyEvent[];
   recording_start_ms: number;
   display_width: number;
   display_height: number;
@@ -42,40 +56,125 @@ export interface ZoomConfig {
   hold_duration_ms: number;
   zoom_out_duration_ms: number;
   easing: EasingType;
+  motion_blur?: boolean;
+  shutter_angle?: number;
+  cluster_gap_ms?: number;
+  cluster_distance?: number;
+}
+
+export interface CursorImage {
+  path: string;
+  hotspot_x: number;
+  hotspot_y: number;
+}
+
+export interface SpotlightRange {
+  start_ms: number;
+  end_ms: number;
 }
 
+export type SpotlightMode = "Off" | "WhileMoving" | { Ranges: { ranges: SpotlightRange[] } };
+
 export interface CursorConfig {
   smoothing: number;
   auto_hide_after_ms: number;
   highlight_clicks: boolean;
   highlight_color: string;
   highlight_radius: number;
+  scale?: number;
+  custom_image?: CursorImage | null;
+  spotlight?: SpotlightMode;
+  spotlight_radius?: number;
+  spotlight_feather?: number;
+  spotlight_dim?: number;
 }
 
+export type ImageFit = "Cover" | "Contain" | "Stretch" | "Tile";
+
+export type GradientKind = "Linear" | "Radial" | "Conic";
+
 export type Background =
   | { Solid: { color: string } }
-  | { Gradient: { colors: string[]; angle: number } }
-  | { Image: { path: string } };
+  | {
+      Gradient: {
+        colors: string[];
+        angle: number;
+        kind?: GradientKind;
+        stops?: number[];
+        center_x?: number;
+        center_y?: number;
+        dither?: boolean;
+      };
+    }
+  | { Image: { path: string; fit?: ImageFit; blur?: number; dim?: number } }
+  | "Transparent";
 
 export interface Shadow {
   offset_x: number;
   offset_y: number;
   blur: number;
+  spread?: number;
   color: string;
   opacity: number;
 }
 
 export type AspectRatio = "Auto" | "Ratio16x9" | "Ratio9x16" | "Ratio1x1";
 
+export type CornerStyle = "Circular" | "Squircle";
+
+export interface Border {
+  width: number;
+  color: string;
+  opacity: number;
+}
+
+export interface KeystrokeConfig {
+  enabled: boolean;
+  display_ms: number;
+  font_size: number;
+}
+
+export interface LutConfig {
+  path: string;
+  strength?: number;
+}
+
+export interface ColorGrade {
+  brightness?: number;
+  contrast?: number;
+  saturation?: number;
+  lut?: LutConfig | null;
+}
+
+export type FrameChrome =
+  | "None"
+  | { Window: { title?: string; dark?: boolean } }
+  | { Browser: { url: string; dark?: boolean } }
+  | "Laptop";
+
+export type WatermarkAnchor = "TopLeft" | "TopRight" | "BottomLeft" | "BottomRight";
+
+export interface Watermark {
+  path: string;
+  anchor?: WatermarkAnchor;
+  margin?: number;
+  scale?: number;
+  opacity?: number;
+}
+
 export interface FrameStyle {
   background: Background;
   padding: number;
   corner_radius: number;
+  corner_style?: CornerStyle;
   shadow: Shadow;
+  border?: Border | null;
+  chrome?: FrameChrome;
+  watermark?: Watermark | null;
   aspect_ratio: AspectRatio;
 }
 
-export type ExportFormat = "Mp4" | "Gif";
+export type ExportFormat = "Mp4" | "Gif" | "WebM" | "ProRes" | "Apng";
 
 export type ExportResolution =
   | "R720p"
@@ -88,6 +187,81 @@ export interface ExportConfig {
   resolution: ExportResolution;
   quality: number;
   output_path: string;
+  worker_count?: number;
+}
+
+export type AnnotationSpace = "Source" | "Canvas";
+
+export type AnnotationKind =
+  | {
+      Text: {
+        text: string;
+        size: number;
+        font_path?: string | null;
+        background?: string | null;
+      };
+    }
+  | { Arrow: { to_x: number; to_y: number; width: number } }
+  | {
+      Rectangle: {
+        width: number;
+        height: number;
+        stroke_width: number;
+        corner_radius?: number;
+        fill?: string | null;
+      };
+    }
+  | { Highlight: { width: number; height: number; corner_radius?: number } };
+
+export interface Annotation {
+  id: string;
+  kind: AnnotationKind;
+  start_ms: number;
+  end_ms: number;
+  x: number;
+  y: number;
+  space?: AnnotationSpace;
+  color: string;
+  opacity?: number;
+  fade_in_ms?: number;
+  fade_out_ms?: number;
+}
+
+export type OverlayPosition = "TopLeft" | "TopRight" | "BottomLeft" | "BottomRight";
+
+export interface OverlayRange {
+  start_ms: number;
+  end_ms: number;
+  mode: "Hidden" | "Enlarged";
+}
+
+export interface OverlayTrack {
+  path: string;
+  offset_ms?: number;
+  position?: OverlayPosition;
+  size?: number;
+  enlarged_size?: number;
+  margin?: number;
+  corner_radius?: number;
+  circle?: boolean;
+  shadow?: Shadow;
+  ranges?: OverlayRange[];
+}
+
+export type RedactionMode =
+  | { Blur: { sigma?: number } }
+  | { Pixelate: { block_size?: number } }
+  | { Fill: { color?: string } };
+
+export interface Redaction {
+  id: string;
+  start_ms: number;
+  end_ms: number;
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  mode?: RedactionMode;
 }
 
 export interface Project {
@@ -102,8 +276,29 @@ export interface Project {
   fps: number;
   zoom_config: ZoomConfig;
   cursor_config: CursorConfig;
+  keystroke_config?: KeystrokeConfig;
+  color_grade?: ColorGrade;
   frame_style: FrameStyle;
   export_config: ExportConfig;
+  annotations?: Annotation[];
+  redactions?: Redaction[];
+  overlay?: OverlayTrack | null;
+  cursor_in_video?: boolean;
+}
+
+export interface ProjectSummary {
+  id: string;
+  name: string;
+  created_at: number;
+  duration_ms: number;
+  thumbnail_path: string | null;
+}
+
+export type KeyCapture = "Off" | "ShortcutsOnly" | "All";
+
+export interface AppSettings {
+  default_watermark?: Watermark | null;
+  key_capture?: KeyCapture;
 }
 
 // --- System Commands ---
@@ -124,6 +319,16 @@ export async function checkFfmpeg(): Promise<boolean> {
   return invoke("check_ffmpeg");
 }
 
+// --- Settings Commands ---
+
+export async function getSettings(): Promise<AppSettings> {
+  return invoke("get_settings");
+}
+
+export async function saveSettings(settings: AppSettings): Promise<void> {
+  return invoke("save_settings", { settings });
+}
+
 // --- Recording Commands ---
 
 export async function startRecording(display: DisplayInfo): Promise<string> {
@@ -174,6 +379,10 @@ export async function listProjects(): Promise<Project[]> {
   return invoke("list_projects");
 }
 
+export async function listProjectSummaries(): Promise<ProjectSummary[]> {
+  return invoke("list_project_summaries");
+}
+
 export async function deleteProject(projectId: string): Promise<void> {
   return invoke("delete_project", { projectId });
 }
@@ -191,6 +400,24 @@ export async function getVideoDuration(videoPath: string): Promise<number> {
   return invoke("get_video_duration", { videoPath });
 }
 
+// --- Preview Commands ---
+
+export async function renderPreviewFrame(
+  projectId: string,
+  timeMs: number,
+  maxWidth: number
+): Promise<ArrayBuffer> {
+  return invoke("render_preview_frame", { projectId, timeMs, maxWidth });
+}
+
+export async function getProjectFilmstrip(
+  projectId: string,
+  frameCount: number,
+  frameHeight: number
+): Promise<string> {
+  return invoke("get_project_filmstrip", { projectId, frameCount, frameHeight });
+}
+
 // --- Event Listeners ---
 
 export function onExportProgress(callback: (progress: number) => void) {
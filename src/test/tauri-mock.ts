@@ -118,6 +118,8 @@ export function createTauriMock() {
     checkPermissions: vi.fn().mockResolvedValue(true),
     getAppVersion: vi.fn().mockResolvedValue("0.1.0"),
     checkFfmpeg: vi.fn().mockResolvedValue(true),
+    getSettings: vi.fn().mockResolvedValue({ key_capture: "Off" }),
+    saveSettings: vi.fn().mockResolvedValue(undefined),
     startRecording: vi.fn().mockResolvedValue("test-project-id"),
     stopRecording: vi.fn().mockResolvedValue(makeProject()),
     getRecordingState: vi.fn().mockResolvedValue({
@@ -133,9 +135,12 @@ export function createTauriMock() {
     updateZoomConfig: vi.fn().mockResolvedValue(undefined),
     updateFrameStyle: vi.fn().mockResolvedValue(undefined),
     listProjects: vi.fn().mockResolvedValue([]),
+    listProjectSummaries: vi.fn().mockResolvedValue([]),
     deleteProject: vi.fn().mockResolvedValue(undefined),
     exportProject: vi.fn().mockResolvedValue("/tmp/export.mp4"),
     getVideoDuration: vi.fn().mockResolvedValue(5000),
+    renderPreviewFrame: vi.fn().mockResolvedValue(new ArrayBuffer(0)),
+    getProjectFilmstrip: vi.fn().mockResolvedValue("/tmp/filmstrip.jpg"),
     onExportProgress: vi.fn().mockResolvedValue(vi.fn()),
     onExportComplete: vi.fn().mockResolvedValue(vi.fn()),
   };